@@ -1,11 +1,10 @@
 use std::cell::RefCell;
-use std::fmt::Error;
 use std::rc::Rc;
 
 use crate::rtpacket::base::Layer;
 use crate::rtpacket::decode::PacketBuilder;
 use crate::rtpacket::error::{ErrorDecodeable, PacketError};
-use crate::rtpacket::error::decodeerror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::{DecodeError, ErrorKind};
 use crate::rtpacket::layertype::LayerType;
 
 /// `BaseLayer` is a convenience struct that holds the data for a layer
@@ -59,20 +58,16 @@ fn decoding_layer_decoder(
 
     match decode_error {
         Ok(_) => {}
-        Err(e) => {
-            match e {
-                PacketError::MethodNotImplemented(e) => {
-                    return Err(DecodeError::new("no decoding layer method found", Error::from(e)));
-                }
-                _ => {
-                    return Err(DecodeError::from(e));
-                }
-            }
+        Err(PacketError::Decode(e)) if e.kind() == ErrorKind::MethodNotImplemented => {
+            return Err(DecodeError::with_kind(
+                ErrorKind::Decode,
+                "no decoding layer method found",
+                Some(Box::new(e)),
+            ));
+        }
+        Err(PacketError::Decode(e)) => {
+            return Err(e);
         }
-    }
-
-    if let Err(e) = decode_error {
-        return Err(e);
     }
 
     packet_builder.add_layer(decoder);
@@ -0,0 +1,148 @@
+use std::rc::Rc;
+
+use erased_serde::Serialize as _;
+
+use crate::rtpacket::base::{ApplicationLayer, Layer};
+use crate::rtpacket::decode::decoder_builder;
+use crate::rtpacket::layertype::LayerType;
+use crate::rtpacket::layertype::LayerTypeID;
+use crate::rtpacket::layertype::LayerTypes::{LayerTypeDecompressedPayload, LayerTypePayload};
+
+/// The compression format a payload was detected (or told) to be encoded
+/// with, see [`crate::rtpacket::decode::decodecompressedpayload`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressionEncoding {
+    Gzip,
+    Deflate,
+    Brotli,
+}
+
+impl CompressionEncoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            CompressionEncoding::Gzip => "gzip",
+            CompressionEncoding::Deflate => "deflate",
+            CompressionEncoding::Brotli => "brotli",
+        }
+    }
+}
+
+/// A layer holding both the still-encoded bytes of a gzip/deflate/brotli-
+/// compressed payload and the inflated bytes decoded from them, recording
+/// which encoding the original bytes were detected as.
+///
+/// `layer_payload` returns the inflated bytes (not `layer_contents`, which
+/// is always `None`, matching its previous behavior), so
+/// `PacketBuilder::next_decoder` picks them up and resumes decoding from the
+/// decompressed data. `layer_contents` can't return the original bytes
+/// instead: `next_decoder` derives a layer's byte offset by walking
+/// backwards from its payload's length within the packet's original frame
+/// buffer, an invariant that only holds when `layer_payload` is itself a
+/// suffix of that buffer — true for ordinary layers, but not for inflated
+/// bytes, which live in an unrelated buffer of a different length. The
+/// original bytes are available via [`original`](Self::original) instead.
+#[derive(Clone)]
+pub struct DecompressedLayer {
+    layer_type: LayerType,
+    encoding: CompressionEncoding,
+    original: Rc<[u8]>,
+    inflated: Rc<[u8]>,
+}
+
+impl DecompressedLayer {
+    pub(crate) fn new(encoding: CompressionEncoding, original: Rc<[u8]>, inflated: Rc<[u8]>) -> Self {
+        DecompressedLayer {
+            layer_type: LayerType {
+                id: LayerTypeDecompressedPayload as LayerTypeID,
+                name: "DecompressedPayload".to_owned(),
+                decoder: decoder_builder(LayerTypeDecompressedPayload),
+            },
+            encoding,
+            original,
+            inflated,
+        }
+    }
+
+    /// The compression format the original payload was detected as.
+    pub fn encoding(&self) -> CompressionEncoding {
+        self.encoding
+    }
+
+    /// The still-encoded bytes this layer was inflated from.
+    pub fn original(&self) -> Rc<[u8]> {
+        self.original.clone()
+    }
+}
+
+impl Layer for DecompressedLayer {
+    fn layer_type(&self) -> LayerType {
+        self.layer_type.clone()
+    }
+
+    fn layer_contents(&self) -> Option<Rc<[u8]>> {
+        None
+    }
+
+    fn layer_payload(&self) -> Option<Rc<[u8]>> {
+        Some(self.inflated.clone())
+    }
+
+    fn string(&self) -> String {
+        format!(
+            "{} decompressed to {} byte(s)",
+            self.encoding.label(),
+            self.inflated.len()
+        )
+    }
+
+    fn serialize_fields(
+        &self,
+        serializer: &mut dyn erased_serde::Serializer,
+    ) -> Result<(), erased_serde::Error> {
+        #[derive(serde::Serialize)]
+        struct Fields<'a> {
+            layer_type: &'a str,
+            encoding: &'static str,
+            original_hex: String,
+            inflated_len: usize,
+        }
+
+        Fields {
+            layer_type: &self.layer_type.name,
+            encoding: self.encoding.label(),
+            original_hex: hex::encode(&self.original),
+            inflated_len: self.inflated.len(),
+        }
+        .erased_serialize(serializer)
+    }
+
+    fn next_layer_type_id(&self) -> Option<LayerTypeID> {
+        Some(LayerTypePayload as LayerTypeID)
+    }
+}
+
+impl ApplicationLayer for DecompressedLayer {
+    fn payload(&self) -> Option<Rc<[u8]>> {
+        Some(self.inflated.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn original_and_layer_payload_expose_the_encoded_and_decoded_forms_respectively() {
+        let original: Rc<[u8]> = Rc::from(&b"compressed"[..]);
+        let inflated: Rc<[u8]> = Rc::from(&b"decompressed"[..]);
+        let layer = DecompressedLayer::new(CompressionEncoding::Gzip, original.clone(), inflated.clone());
+
+        assert_eq!(layer.original().as_ref(), original.as_ref());
+        assert_eq!(layer.layer_payload().as_deref(), Some(inflated.as_ref()));
+        assert!(
+            layer.layer_contents().is_none(),
+            "layer_contents must stay None so next_decoder's byte-offset math isn't fed the \
+             inflated-buffer-relative original bytes"
+        );
+    }
+}
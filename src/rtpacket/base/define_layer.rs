@@ -0,0 +1,693 @@
+//! Helper macros for generating fixed-field header layers.
+//!
+//! Hand-writing `serialize_to`, `layer_type`, and `decode_from_bytes` for
+//! every protocol the way [`Payload`](crate::rtpacket::base::payload::Payload)
+//! does is tedious and error-prone once a layer has more than a field or two.
+//! [`define_layer!`] generates all of that from a declarative field list.
+//!
+//! Adding a real protocol on top of this still requires its own
+//! [`LayerTypes`](crate::rtpacket::layertype::LayerTypes) variant and a
+//! [`register_decoder`](crate::rtpacket::layertype::register_decoder) call
+//! for it, exactly as `Payload`'s `LayerTypePayload` has in
+//! [`register_defaults`](crate::rtpacket::layertype::register_defaults) —
+//! `define_layer!` only generates the struct and its
+//! `Layer`/`SerializableLayer`/`Payloadable` impls.
+
+/// Maps a wire field type to the Rust type used to hold its decoded value.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_rust_type {
+    (u8) => {
+        u8
+    };
+    (u16be) => {
+        u16
+    };
+    (u32be) => {
+        u32
+    };
+    ([u8; $n:expr]) => {
+        [u8; $n]
+    };
+}
+
+/// Returns the on-the-wire width, in bytes, of a field type.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_field_width {
+    (u8) => {
+        1usize
+    };
+    (u16be) => {
+        2usize
+    };
+    (u32be) => {
+        4usize
+    };
+    ([u8; $n:expr]) => {
+        $n
+    };
+}
+
+/// Returns the zero value used to initialize a field in `new()`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_zero_value {
+    (u8) => {
+        0u8
+    };
+    (u16be) => {
+        0u16
+    };
+    (u32be) => {
+        0u32
+    };
+    ([u8; $n:expr]) => {
+        [0u8; $n]
+    };
+}
+
+/// Writes `$value` big-endian into `$header` at `$cursor`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_write_field {
+    (u8, $header:expr, $cursor:expr, $value:expr) => {
+        $header[$cursor] = $value;
+    };
+    (u16be, $header:expr, $cursor:expr, $value:expr) => {
+        $header[$cursor..$cursor + 2].copy_from_slice(&$value.to_be_bytes());
+    };
+    (u32be, $header:expr, $cursor:expr, $value:expr) => {
+        $header[$cursor..$cursor + 4].copy_from_slice(&$value.to_be_bytes());
+    };
+    ([u8; $n:expr], $header:expr, $cursor:expr, $value:expr) => {
+        $header[$cursor..$cursor + $n].copy_from_slice(&$value);
+    };
+}
+
+/// Reads a field's value big-endian out of `$data` at `$cursor`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_read_field {
+    (u8, $data:expr, $cursor:expr) => {
+        $data[$cursor]
+    };
+    (u16be, $data:expr, $cursor:expr) => {
+        u16::from_be_bytes([$data[$cursor], $data[$cursor + 1]])
+    };
+    (u32be, $data:expr, $cursor:expr) => {
+        u32::from_be_bytes([
+            $data[$cursor],
+            $data[$cursor + 1],
+            $data[$cursor + 2],
+            $data[$cursor + 3],
+        ])
+    };
+    ([u8; $n:expr], $data:expr, $cursor:expr) => {{
+        let mut array = [0u8; $n];
+        array.copy_from_slice(&$data[$cursor..$cursor + $n]);
+        array
+    }};
+}
+
+/// Maps a field type to the type used to represent it in the `serde` view
+/// generated for `Layer::serialize_fields`. Fixed-size byte arrays are hex
+/// encoded, matching `Payload`/`DecodeFailure`'s `contents_hex` convention.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_serde_type {
+    (u8) => {
+        u8
+    };
+    (u16be) => {
+        u16
+    };
+    (u32be) => {
+        u32
+    };
+    ([u8; $n:expr]) => {
+        String
+    };
+}
+
+/// Converts a field's decoded value into its `serde` view (see
+/// `__define_layer_serde_type!`).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_serde_value {
+    (u8, $value:expr) => {
+        $value
+    };
+    (u16be, $value:expr) => {
+        $value
+    };
+    (u32be, $value:expr) => {
+        $value
+    };
+    ([u8; $n:expr], $value:expr) => {
+        hex::encode($value)
+    };
+}
+
+/// Writes one field into the header during `serialize_to`, dispatching on
+/// whether the field carries a `#[length]`/`#[checksum]` attribute.
+///
+/// `$checksum_patch` is an `&mut Option<(usize, usize)>` that records the
+/// `(offset, width)` of a `#[checksum]` field so it can be filled in after
+/// every field (including itself, zeroed) has been written, since a
+/// checksum must cover the whole header.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __define_layer_emit_field {
+    (#[length] $field:ident : $ty:tt, $header:expr, $cursor:expr, $self_:expr, $total_len:expr, $options:expr, $checksum_patch:expr) => {{
+        let width = $crate::__define_layer_field_width!($ty);
+        let value: $crate::__define_layer_rust_type!($ty) = if $options.fix_lengths {
+            let fits = match width {
+                1 => $total_len <= u8::MAX as usize,
+                2 => $crate::rtpacket::writer::fits_in_u16($total_len),
+                _ => $crate::rtpacket::writer::fits_in_u32($total_len),
+            };
+            if !fits {
+                return Err(Box::new(
+                    $crate::rtpacket::writer::SerializeError::length_overflow(
+                        $self_.layer_type.name.clone(),
+                        (width * 8) as u8,
+                        $total_len,
+                    ),
+                ));
+            }
+            $total_len as $crate::__define_layer_rust_type!($ty)
+        } else {
+            $self_.$field
+        };
+        $crate::__define_layer_write_field!($ty, $header, $cursor, value);
+    }};
+    (#[checksum] $field:ident : $ty:tt, $header:expr, $cursor:expr, $self_:expr, $total_len:expr, $options:expr, $checksum_patch:expr) => {{
+        if $options.compute_checksums {
+            $crate::__define_layer_write_field!(
+                $ty,
+                $header,
+                $cursor,
+                $crate::__define_layer_zero_value!($ty)
+            );
+            *$checksum_patch = Some(($cursor, $crate::__define_layer_field_width!($ty)));
+        } else {
+            $crate::__define_layer_write_field!($ty, $header, $cursor, $self_.$field);
+        }
+    }};
+    ($field:ident : $ty:tt, $header:expr, $cursor:expr, $self_:expr, $total_len:expr, $options:expr, $checksum_patch:expr) => {{
+        $crate::__define_layer_write_field!($ty, $header, $cursor, $self_.$field);
+    }};
+}
+
+/// Generates a fixed-field header layer: the struct itself, plus its `Layer`,
+/// `SerializableLayer`, and `Payloadable` implementations.
+///
+/// # Field types
+///
+/// Each field is exactly one of: `u8`, `u16be`, `u32be` (big-endian), or
+/// `[u8; N]` for a fixed-size run of bytes (e.g. a MAC address).
+///
+/// # Field attributes
+///
+/// * `#[length]` — when `SerializeOptions::fix_lengths` is set, this field is
+///   recomputed from the header length plus the wrapped payload's length
+///   instead of using the struct's stored value, returning
+///   [`SerializeError::length_overflow`](crate::rtpacket::writer::SerializeError::length_overflow)
+///   if it doesn't fit in the field's width.
+/// * `#[checksum]` — zeroed while writing the header, then filled in (via
+///   [`compute_layer_checksum`](crate::rtpacket::checksum::compute_layer_checksum)
+///   over the written header and the wrapped payload) once
+///   `SerializeOptions::compute_checksums` is set. At most one field per
+///   layer should carry this attribute.
+///
+/// # Dispatching to the next layer
+///
+/// By default, `Payloadable::next_layer_type` (and so
+/// `Layer::next_layer_type_id`) reports `LayerTypeZero`/"Unknown" and falls
+/// back to the generic fragment/`DecodeFailure` handling, exactly like
+/// [`Payload`](crate::rtpacket::base::payload::Payload). Protocols whose
+/// whole point is dispatching to a different next layer by field value (an
+/// Ethernet `EtherType`, an IPv4 `Protocol` number, ...) supply an optional
+/// trailing `next_layer_type: |$self| $body` clause instead, where `$body`
+/// is any expression evaluating to a `LayerType` and `$self` is bound to
+/// `&Self`:
+///
+/// ```ignore
+/// define_layer! {
+///     struct ExampleHeader {
+///         layer_type: crate::rtpacket::layertype::LayerTypes::LayerTypePayload,
+///         name: "Example",
+///         fields: {
+///             #[length]
+///             total_len: u16be,
+///             #[checksum]
+///             checksum: u16be,
+///             flags: u8,
+///             next_type: u16be,
+///         },
+///         next_layer_type: |header| match header.next_type {
+///             0x0800 => crate::rtpacket::layertype::LayerType {
+///                 id: crate::rtpacket::layertype::LayerTypes::LayerTypePayload as _,
+///                 name: "IPv4".to_owned(),
+///                 decoder: crate::rtpacket::decode::decoder_builder(
+///                     crate::rtpacket::layertype::LayerTypes::LayerTypePayload,
+///                 ),
+///             },
+///             _ => crate::rtpacket::layertype::LayerType {
+///                 id: crate::rtpacket::layertype::LayerTypes::LayerTypeZero as _,
+///                 name: "Unknown".to_owned(),
+///                 decoder: crate::rtpacket::decode::decoder_builder(
+///                     crate::rtpacket::layertype::LayerTypes::LayerTypeDecodeFailure,
+///                 ),
+///             },
+///         }
+///     }
+/// }
+/// ```
+///
+/// A real protocol layer must also add its own
+/// [`LayerTypes`](crate::rtpacket::layertype::LayerTypes) variant and
+/// register its decoder via
+/// [`register_decoder`](crate::rtpacket::layertype::register_decoder); this
+/// macro only generates the layer struct and its trait impls.
+#[macro_export]
+macro_rules! define_layer {
+    (
+        struct $name:ident {
+            layer_type: $layer_type_variant:expr,
+            name: $layer_name:expr,
+            fields: {
+                $( $(#[$attr:ident])? $field_name:ident : $field_ty:tt ),* $(,)?
+            }
+            $(, next_layer_type: |$next_self:ident| $next_layer_body:expr)? $(,)?
+        }
+    ) => {
+        #[derive(Clone)]
+        pub struct $name {
+            layer_type: $crate::rtpacket::layertype::LayerType,
+            $( pub $field_name: $crate::__define_layer_rust_type!($field_ty), )*
+            payload: Option<std::rc::Rc<[u8]>>,
+        }
+
+        impl $name {
+            /// Creates a new `$name` with every field zeroed and no payload.
+            pub fn new() -> Self {
+                $name {
+                    layer_type: $crate::rtpacket::layertype::LayerType {
+                        id: $layer_type_variant as $crate::rtpacket::layertype::LayerTypeID,
+                        name: $layer_name.to_owned(),
+                        decoder: $crate::rtpacket::decode::decoder_builder($layer_type_variant),
+                    },
+                    $( $field_name: $crate::__define_layer_zero_value!($field_ty), )*
+                    payload: None,
+                }
+            }
+        }
+
+        impl $crate::rtpacket::base::Layer for $name {
+            fn layer_type(&self) -> $crate::rtpacket::layertype::LayerType {
+                self.layer_type.clone()
+            }
+
+            fn layer_contents(&self) -> Option<std::rc::Rc<[u8]>> {
+                None
+            }
+
+            fn layer_payload(&self) -> Option<std::rc::Rc<[u8]>> {
+                self.payload.clone()
+            }
+
+            fn string(&self) -> String {
+                format!(
+                    "{}: {} byte(s) of payload",
+                    $layer_name,
+                    self.payload.as_deref().map(|p| p.len()).unwrap_or(0)
+                )
+            }
+
+            fn as_serializable(&self) -> Option<std::rc::Rc<dyn $crate::rtpacket::writer::SerializableLayer>> {
+                Some(std::rc::Rc::new(self.clone()))
+            }
+
+            fn serialize_fields(
+                &self,
+                serializer: &mut dyn erased_serde::Serializer,
+            ) -> Result<(), erased_serde::Error> {
+                #[derive(serde::Serialize)]
+                struct Fields<'a> {
+                    layer_type: &'a str,
+                    $( $field_name: $crate::__define_layer_serde_type!($field_ty), )*
+                }
+
+                <Fields as erased_serde::Serialize>::erased_serialize(
+                    &Fields {
+                        layer_type: self.layer_type.name.as_str(),
+                        $( $field_name: $crate::__define_layer_serde_value!($field_ty, self.$field_name), )*
+                    },
+                    serializer,
+                )
+            }
+
+            fn next_layer_type_id(&self) -> Option<$crate::rtpacket::layertype::LayerTypeID> {
+                Some(<Self as $crate::rtpacket::base::Payloadable>::next_layer_type(self).id)
+            }
+        }
+
+        impl $crate::rtpacket::writer::SerializableLayer for $name {
+            fn serialize_to(
+                &self,
+                buffer: &mut $crate::rtpacket::writer::SerializeBuffer,
+                options: $crate::rtpacket::writer::SerializeOptions,
+            ) -> Result<(), Box<dyn std::error::Error>> {
+                use $crate::rtpacket::writer::SerializeableBuffer;
+
+                let header_len: usize = 0 $( + $crate::__define_layer_field_width!($field_ty) )*;
+
+                // Captured before `prepend_bytes` so the immutable borrow of
+                // `buffer` from `bytes()` ends before the mutable one begins.
+                let wrapped_payload = buffer.bytes().to_vec();
+                let total_len = header_len + wrapped_payload.len();
+
+                let header = buffer.prepend_bytes(header_len)?;
+                let mut cursor = 0usize;
+                let mut checksum_patch: Option<(usize, usize)> = None;
+
+                $(
+                    $crate::__define_layer_emit_field!(
+                        $(#[$attr])? $field_name : $field_ty,
+                        header, cursor, self, total_len, options, &mut checksum_patch
+                    );
+                    cursor += $crate::__define_layer_field_width!($field_ty);
+                )*
+
+                if options.compute_checksums {
+                    if let Some((offset, width)) = checksum_patch {
+                        let checksum_value = $crate::rtpacket::checksum::compute_layer_checksum(
+                            $crate::rtpacket::checksum::Checksum::new(),
+                            header,
+                            &wrapped_payload,
+                        );
+                        match width {
+                            1 => header[offset] = checksum_value as u8,
+                            2 => header[offset..offset + 2]
+                                .copy_from_slice(&checksum_value.to_be_bytes()),
+                            _ => header[offset..offset + 4]
+                                .copy_from_slice(&(checksum_value as u32).to_be_bytes()),
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+
+            fn layer_type(&self) -> $crate::rtpacket::layertype::LayerType {
+                self.layer_type.clone()
+            }
+
+            fn constraints(&self) -> $crate::rtpacket::writer::LayerConstraints {
+                $crate::rtpacket::writer::LayerConstraints {
+                    header_len: 0 $( + $crate::__define_layer_field_width!($field_ty) )*,
+                    ..Default::default()
+                }
+            }
+        }
+
+        impl $crate::rtpacket::base::Payloadable for $name {
+            fn can_decode(&self) -> impl $crate::rtpacket::layerclass::LayerClass {
+                self.layer_type.clone()
+            }
+
+            fn next_layer_type(&self) -> $crate::rtpacket::layertype::LayerType {
+                $(
+                    let $next_self = self;
+                    return $next_layer_body;
+                )?
+                #[allow(unreachable_code)]
+                {
+                    $crate::rtpacket::layertype::LayerType {
+                        id: $crate::rtpacket::layertype::LayerTypes::LayerTypeZero
+                            as $crate::rtpacket::layertype::LayerTypeID,
+                        name: "Unknown".to_owned(),
+                        decoder: $crate::rtpacket::decode::decoder_builder(
+                            $crate::rtpacket::layertype::LayerTypes::LayerTypeDecodeFailure,
+                        ),
+                    }
+                }
+            }
+
+            fn decode_from_bytes(
+                &mut self,
+                data: std::rc::Rc<[u8]>,
+                _decoder: std::rc::Rc<dyn $crate::rtpacket::decode::DecodeFeedback>,
+            ) -> Result<(), $crate::rtpacket::error::packetdecodeerror::DecodeError> {
+                use $crate::rtpacket::error::ErrorDecodeable;
+
+                let header_len: usize = 0 $( + $crate::__define_layer_field_width!($field_ty) )*;
+
+                if data.len() < header_len {
+                    return Err($crate::rtpacket::error::packetdecodeerror::DecodeError::new(
+                        &format!(
+                            "{}: expected at least {} byte(s) of header, got {}",
+                            $layer_name,
+                            header_len,
+                            data.len()
+                        ),
+                        None,
+                    ));
+                }
+
+                let mut cursor = 0usize;
+                $(
+                    self.$field_name = $crate::__define_layer_read_field!($field_ty, data, cursor);
+                    cursor += $crate::__define_layer_field_width!($field_ty);
+                )*
+
+                self.payload = Some(std::rc::Rc::from(&data[header_len..]));
+
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::rtpacket::base::payload::Payload;
+    use crate::rtpacket::base::{Layer, Payloadable};
+    use crate::rtpacket::decode::nildecodefeedback::NilDecodeFeedback;
+    use crate::rtpacket::layertype::LayerTypes::LayerTypePayload;
+    use crate::rtpacket::writer::{
+        SerializeBuffer, SerializeOptions, SerializeableBuffer, SerializableLayer,
+    };
+
+    // A toy header exercising every field kind the macro supports. It reuses
+    // `LayerTypePayload` purely so the test doesn't need a real protocol's
+    // own `LayerTypes` variant and registered decoder; a real layer would add
+    // both, the same way `Payload` owns `LayerTypePayload`.
+    define_layer! {
+        struct TestHeader {
+            layer_type: LayerTypePayload,
+            name: "TestHeader",
+            fields: {
+                #[length]
+                total_len: u16be,
+                #[checksum]
+                checksum: u16be,
+                flags: u8,
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_to_leaves_stored_values_when_options_are_default() {
+        let mut header = TestHeader::new();
+        header.total_len = 0xABCD;
+        header.checksum = 0xBEEF;
+        header.flags = 7;
+
+        let mut buffer = SerializeBuffer::new();
+        header
+            .serialize_to(&mut buffer, SerializeOptions::default())
+            .expect("serialization should succeed");
+
+        assert_eq!(buffer.bytes(), [0xAB, 0xCD, 0xBE, 0xEF, 7]);
+    }
+
+    #[test]
+    fn serialize_to_fixes_length_and_computes_checksum() {
+        let mut header = TestHeader::new();
+        header.flags = 0x11;
+
+        let mut buffer = SerializeBuffer::new();
+        buffer.prepend_bytes(3).unwrap().copy_from_slice(&[1, 2, 3]);
+
+        header
+            .serialize_to(
+                &mut buffer,
+                SerializeOptions {
+                    fix_lengths: true,
+                    compute_checksums: true,
+                },
+            )
+            .expect("serialization should succeed");
+
+        let bytes = buffer.bytes();
+        // Header (5 bytes) + the 3-byte payload that was already in the buffer.
+        assert_eq!(u16::from_be_bytes([bytes[0], bytes[1]]), 8);
+        assert_eq!(bytes[4], 0x11);
+        assert_eq!(&bytes[5..], &[1, 2, 3]);
+
+        let mut manual = crate::rtpacket::checksum::Checksum::new();
+        let mut header_with_zeroed_checksum = bytes[..5].to_vec();
+        header_with_zeroed_checksum[2] = 0;
+        header_with_zeroed_checksum[3] = 0;
+        manual.add_bytes(&header_with_zeroed_checksum);
+        manual.add_bytes(&[1, 2, 3]);
+        assert_eq!(u16::from_be_bytes([bytes[2], bytes[3]]), manual.checksum());
+    }
+
+    #[test]
+    fn serialize_to_rejects_length_overflow() {
+        // `total_len` is a 16-bit field; ask it to describe a header plus a
+        // payload far larger than `u16::MAX`.
+        let header = TestHeader::new();
+        let mut buffer = SerializeBuffer::new();
+        buffer.append_bytes(u16::MAX as usize).unwrap();
+
+        let result = header.serialize_to(
+            &mut buffer,
+            SerializeOptions {
+                fix_lengths: true,
+                compute_checksums: false,
+            },
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn decode_from_bytes_round_trips_serialize_to() {
+        let mut header = TestHeader::new();
+        header.total_len = 99;
+        header.checksum = 0x1234;
+        header.flags = 0x42;
+
+        let mut buffer = SerializeBuffer::new();
+        header
+            .serialize_to(&mut buffer, SerializeOptions::default())
+            .unwrap();
+        let encoded: Rc<[u8]> = Rc::from(buffer.bytes());
+
+        let mut decoded = TestHeader::new();
+        decoded
+            .decode_from_bytes(encoded.clone(), Rc::new(NilDecodeFeedback))
+            .expect("decode should succeed");
+
+        assert_eq!(decoded.total_len, 99);
+        assert_eq!(decoded.checksum, 0x1234);
+        assert_eq!(decoded.flags, 0x42);
+        assert_eq!(decoded.layer_payload(), None);
+    }
+
+    #[test]
+    fn decode_from_bytes_exposes_trailing_bytes_as_payload() {
+        let mut decoded = TestHeader::new();
+        let data: Rc<[u8]> = Rc::from(&[0, 5, 0, 0, 1, b'h', b'i'][..]);
+
+        decoded
+            .decode_from_bytes(data, Rc::new(NilDecodeFeedback))
+            .expect("decode should succeed");
+
+        assert_eq!(decoded.layer_payload().as_deref(), Some(&b"hi"[..]));
+    }
+
+    #[test]
+    fn decode_from_bytes_rejects_truncated_header() {
+        let mut decoded = TestHeader::new();
+        let data: Rc<[u8]> = Rc::from(&[0, 1][..]);
+
+        assert!(decoded
+            .decode_from_bytes(data, Rc::new(NilDecodeFeedback))
+            .is_err());
+    }
+
+    // Not exercised directly above, but `Payload` stays the baseline sanity
+    // check that the hand-written equivalent this macro replaces still works.
+    #[test]
+    fn payload_still_round_trips_for_comparison() {
+        let data: Rc<[u8]> = Rc::from([1u8, 2, 3]);
+        let payload = Payload::new_from(data.clone());
+        assert_eq!(payload.string(), "3 byte(s)");
+    }
+
+    #[test]
+    fn constraints_reports_the_generated_headers_real_length() {
+        use crate::rtpacket::writer::LayerConstraints;
+
+        let header = TestHeader::new();
+        let constraints = header.constraints();
+        assert_eq!(constraints.header_len, 5, "total_len + checksum + flags is 2 + 2 + 1 bytes");
+        assert_eq!(constraints.footer_len, LayerConstraints::default().footer_len);
+        assert_eq!(constraints.max_body_len, LayerConstraints::default().max_body_len);
+    }
+
+    // A header whose next layer depends on a field's value, the way a real
+    // Ethernet/IPv4-style protocol would use `next_layer_type` for.
+    define_layer! {
+        struct DispatchingHeader {
+            layer_type: LayerTypePayload,
+            name: "DispatchingHeader",
+            fields: {
+                next_type: u8,
+            },
+            next_layer_type: |header| match header.next_type {
+                1 => crate::rtpacket::layertype::LayerType {
+                    id: LayerTypePayload as crate::rtpacket::layertype::LayerTypeID,
+                    name: "Payload".to_owned(),
+                    decoder: crate::rtpacket::decode::decoder_builder(LayerTypePayload),
+                },
+                _ => crate::rtpacket::layertype::LayerType {
+                    id: crate::rtpacket::layertype::LayerTypes::LayerTypeZero
+                        as crate::rtpacket::layertype::LayerTypeID,
+                    name: "Unknown".to_owned(),
+                    decoder: crate::rtpacket::decode::decoder_builder(
+                        crate::rtpacket::layertype::LayerTypes::LayerTypeDecodeFailure,
+                    ),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn next_layer_type_dispatches_by_field_value_when_overridden() {
+        let mut header = DispatchingHeader::new();
+        header.next_type = 1;
+        assert_eq!(header.next_layer_type().id, LayerTypePayload as crate::rtpacket::layertype::LayerTypeID);
+        assert_eq!(
+            header.next_layer_type_id(),
+            Some(LayerTypePayload as crate::rtpacket::layertype::LayerTypeID)
+        );
+    }
+
+    #[test]
+    fn next_layer_type_falls_back_to_unknown_when_the_field_is_unrecognized() {
+        let mut header = DispatchingHeader::new();
+        header.next_type = 0xFF;
+        assert_eq!(
+            header.next_layer_type().id,
+            crate::rtpacket::layertype::LayerTypes::LayerTypeZero as crate::rtpacket::layertype::LayerTypeID
+        );
+    }
+
+    #[test]
+    fn next_layer_type_defaults_to_unknown_when_not_overridden() {
+        let header = TestHeader::new();
+        assert_eq!(
+            header.next_layer_type().id,
+            crate::rtpacket::layertype::LayerTypes::LayerTypeZero as crate::rtpacket::layertype::LayerTypeID
+        );
+    }
+}
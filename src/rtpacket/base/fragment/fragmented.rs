@@ -0,0 +1,82 @@
+use std::rc::Rc;
+
+/// A buffer backed by one or more non-contiguous byte slices.
+///
+/// Exists so code building a frame out of several owned regions (e.g. a
+/// header prepended to a borrowed payload, or pieces pulled out of a
+/// reassembled datagram) never has to copy them into one allocation just to
+/// satisfy an API that wants a single contiguous slice.
+pub trait FragmentedBuffer {
+    /// Total length across all constituent slices.
+    fn len(&self) -> usize;
+
+    /// Whether this buffer has no bytes at all.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the constituent slices, in order.
+    fn slices(&self) -> std::slice::Iter<'_, Rc<[u8]>>;
+
+    /// Copies every slice into one newly allocated, contiguous buffer.
+    fn copy_into_contiguous(&self) -> Rc<[u8]> {
+        let mut contiguous = Vec::with_capacity(self.len());
+        for slice in self.slices() {
+            contiguous.extend_from_slice(slice);
+        }
+        Rc::from(contiguous)
+    }
+}
+
+/// An ordered collection of byte slices treated as one logical buffer,
+/// without copying them together.
+#[derive(Clone)]
+pub struct FragmentedBytes {
+    slices: Vec<Rc<[u8]>>,
+}
+
+impl FragmentedBytes {
+    /// Creates a `FragmentedBytes` from the given slices, in order.
+    pub fn new(slices: Vec<Rc<[u8]>>) -> Self {
+        FragmentedBytes { slices }
+    }
+}
+
+impl FragmentedBuffer for FragmentedBytes {
+    fn len(&self) -> usize {
+        self.slices.iter().map(|slice| slice.len()).sum()
+    }
+
+    fn slices(&self) -> std::slice::Iter<'_, Rc<[u8]>> {
+        self.slices.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn len_sums_every_slice() {
+        let bytes = FragmentedBytes::new(vec![Rc::from(&[1, 2][..]), Rc::from(&[3, 4, 5][..])]);
+        assert_eq!(bytes.len(), 5);
+        assert!(!bytes.is_empty());
+    }
+
+    #[test]
+    fn empty_fragmented_bytes_is_empty() {
+        let bytes = FragmentedBytes::new(vec![]);
+        assert_eq!(bytes.len(), 0);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn copy_into_contiguous_preserves_order() {
+        let bytes = FragmentedBytes::new(vec![
+            Rc::from(&[1, 2][..]),
+            Rc::from(&[][..]),
+            Rc::from(&[3, 4, 5][..]),
+        ]);
+        assert_eq!(bytes.copy_into_contiguous().as_ref(), &[1, 2, 3, 4, 5]);
+    }
+}
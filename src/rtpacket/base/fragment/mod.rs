@@ -1,12 +1,15 @@
+use std::cell::RefCell;
 use std::error::Error;
 use std::io;
 use std::ops::Deref;
 use std::rc::Rc;
 
+use erased_serde::Serialize as _;
+
 use crate::rtpacket::base::{ApplicationLayer, Layer, Payloadable};
 use crate::rtpacket::decode::{DecodeFeedback, decoder_builder, LayerType};
 use crate::rtpacket::decode::decodefragment::fragment_decoder;
-use crate::rtpacket::error::decodererror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
 use crate::rtpacket::layerclass::LayerClass;
 use crate::rtpacket::layertype::LayerTypeID;
 use crate::rtpacket::layertype::LayerTypes::{LayerTypeFragment, LayerTypeZero};
@@ -14,11 +17,41 @@ use crate::rtpacket::writer::{
     SerializableLayer, SerializeableBuffer, SerializeBuffer, SerializeOptions,
 };
 
+pub mod fragmented;
+pub mod reassembler;
+pub mod vector;
+pub use fragmented::{FragmentedBuffer, FragmentedBytes};
+pub use reassembler::{FlowKey, FragmentReassembler};
+pub use vector::{FragmentVector, FragmentVectorError, FragmentVectorView};
+
+/// How a `Fragment`'s bytes are actually stored.
+#[derive(Clone)]
+enum FragmentData {
+    /// A single, already-contiguous buffer.
+    Contiguous(Rc<[u8]>),
+    /// Several slices that together make up the fragment's bytes, kept apart
+    /// to avoid copying them together until a contiguous view is demanded.
+    Fragmented(FragmentedBytes),
+}
+
+impl FragmentData {
+    fn len(&self) -> usize {
+        match self {
+            FragmentData::Contiguous(data) => data.len(),
+            FragmentData::Fragmented(data) => data.len(),
+        }
+    }
+}
+
 // Structure representing a fragment of a larger frame.
 #[derive(Clone)]
 pub struct Fragment {
     layer_type: LayerType,
-    in_data: Option<Rc<[u8]>>,
+    in_data: Option<FragmentData>,
+    /// Lazily-materialized contiguous view of `in_data` when it's
+    /// `Fragmented`, so repeated calls to `layer_contents()` don't re-copy
+    /// the slices together every time.
+    contiguous_cache: RefCell<Option<Rc<[u8]>>>,
 }
 
 impl Fragment {
@@ -34,6 +67,7 @@ impl Fragment {
                 decoder: fragment_decoder(),
             },
             in_data: None,
+            contiguous_cache: RefCell::new(None),
         }
     }
 
@@ -53,7 +87,31 @@ impl Fragment {
                 name: "DecodeFragment".to_owned(),
                 decoder: decoder_builder(LayerTypeFragment),
             },
-            in_data: Some(data),
+            in_data: Some(FragmentData::Contiguous(data)),
+            contiguous_cache: RefCell::new(None),
+        }
+    }
+
+    /// Creates a new `Fragment` backed by several owned slices, without
+    /// copying them together.
+    ///
+    /// # Arguments
+    ///
+    /// * `slices` - The slices making up the fragment's bytes, in order.
+    ///
+    /// # Returns
+    ///
+    /// A new `Fragment` whose bytes are these slices, gathered into place
+    /// only when serialized or when a contiguous view is requested.
+    pub(crate) fn new_from_slices(slices: Vec<Rc<[u8]>>) -> Self {
+        Fragment {
+            layer_type: LayerType {
+                id: LayerTypeFragment as LayerTypeID,
+                name: "DecodeFragment".to_owned(),
+                decoder: decoder_builder(LayerTypeFragment),
+            },
+            in_data: Some(FragmentData::Fragmented(FragmentedBytes::new(slices))),
+            contiguous_cache: RefCell::new(None),
         }
     }
 }
@@ -64,7 +122,18 @@ impl Layer for Fragment {
     }
 
     fn layer_contents(&self) -> Option<Rc<[u8]>> {
-        self.in_data.clone()
+        match &self.in_data {
+            None => None,
+            Some(FragmentData::Contiguous(data)) => Some(data.clone()),
+            Some(FragmentData::Fragmented(data)) => {
+                if let Some(cached) = self.contiguous_cache.borrow().as_ref() {
+                    return Some(cached.clone());
+                }
+                let materialized = data.copy_into_contiguous();
+                *self.contiguous_cache.borrow_mut() = Some(materialized.clone());
+                Some(materialized)
+            }
+        }
     }
 
     fn layer_payload(&self) -> Option<Rc<[u8]>> {
@@ -74,9 +143,34 @@ impl Layer for Fragment {
     fn string(&self) -> String {
         match &self.in_data {
             None => "0 byte(s)".to_string(),
-            Some(data) => format!("{} byte(s)", data.deref().len()),
+            Some(data) => format!("{} byte(s)", data.len()),
         }
     }
+
+    fn as_serializable(&self) -> Option<Rc<dyn SerializableLayer>> {
+        Some(Rc::new(self.clone()))
+    }
+
+    fn serialize_fields(
+        &self,
+        serializer: &mut dyn erased_serde::Serializer,
+    ) -> Result<(), erased_serde::Error> {
+        #[derive(serde::Serialize)]
+        struct Fields<'a> {
+            layer_type: &'a str,
+            contents_hex: Option<String>,
+        }
+
+        Fields {
+            layer_type: &self.layer_type.name,
+            contents_hex: self.layer_contents().as_deref().map(hex::encode),
+        }
+        .erased_serialize(serializer)
+    }
+
+    fn next_layer_type_id(&self) -> Option<LayerTypeID> {
+        Some(Payloadable::next_layer_type(self).id)
+    }
 }
 
 impl ApplicationLayer for Fragment {
@@ -107,12 +201,21 @@ impl SerializableLayer for Fragment {
                 io::ErrorKind::Other,
                 "in_data is empty",
             ))),
-            Some(data) => {
+            Some(FragmentData::Contiguous(data)) => {
                 let size = data.deref().len();
                 let bytes = buffer.prepend_bytes(size)?;
                 bytes.copy_from_slice(&data.deref());
                 Ok(())
             }
+            Some(FragmentData::Fragmented(data)) => {
+                let bytes = buffer.prepend_bytes(data.len())?;
+                let mut offset = 0;
+                for slice in data.slices() {
+                    bytes[offset..offset + slice.len()].copy_from_slice(slice);
+                    offset += slice.len();
+                }
+                Ok(())
+            }
         }
     }
 
@@ -137,9 +240,9 @@ impl Payloadable for Fragment {
     fn decode_from_bytes(
         &mut self,
         data: Rc<[u8]>,
-        mut _builder: Box<dyn DecodeFeedback>,
+        mut _builder: Rc<dyn DecodeFeedback>,
     ) -> Result<(), DecodeError> {
-        self.in_data = Option::from(data.clone());
+        self.in_data = Some(FragmentData::Contiguous(data.clone()));
 
         Ok(())
     }
@@ -150,7 +253,7 @@ impl std::fmt::Display for Fragment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.in_data {
             None => write!(f, "0 byte(s)"),
-            Some(data) => write!(f, "{} byte(s)", data.len()), // Uses automatic dereferencing
+            Some(data) => write!(f, "{} byte(s)", data.len()),
         }
     }
 }
@@ -312,4 +415,42 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn new_from_slices_gathers_into_layer_contents() {
+        let fragment = Fragment::new_from_slices(vec![
+            Rc::from(&[1, 2][..]),
+            Rc::from(&[][..]),
+            Rc::from(&[3, 4, 5][..]),
+        ]);
+
+        assert_eq!(fragment.string(), "5 byte(s)");
+        assert_eq!(
+            fragment.layer_contents().unwrap().as_ref(),
+            &[1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn layer_contents_caches_the_materialized_view_for_fragmented_data() {
+        let fragment = Fragment::new_from_slices(vec![Rc::from(&[1, 2][..]), Rc::from(&[3][..])]);
+
+        let first = fragment.layer_contents().unwrap();
+        let second = fragment.layer_contents().unwrap();
+        assert!(
+            Rc::ptr_eq(&first, &second),
+            "repeated calls should reuse the cached materialized buffer"
+        );
+    }
+
+    #[test]
+    fn serialize_to_gathers_slices_without_a_length_prefix() {
+        let fragment = Fragment::new_from_slices(vec![Rc::from(&[1, 2][..]), Rc::from(&[3, 4, 5][..])]);
+        let mut buffer = SerializeBuffer::new();
+        let opts = SerializeOptions::default();
+
+        fragment.serialize_to(&mut buffer, opts).unwrap();
+
+        assert_eq!(buffer.bytes(), &[1, 2, 3, 4, 5]);
+    }
 }
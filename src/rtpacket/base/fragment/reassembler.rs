@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::rtpacket::base::fragment::Fragment;
+
+/// Identifies the datagram a fragment belongs to, so fragments can be routed
+/// to the right reassembly in progress. For IPv4 this is the source and
+/// destination address, the protocol number, and the 16-bit identification
+/// field (widened to `u32`); for IPv6 it's the source/destination address
+/// and the 32-bit identification field from the Fragment extension header
+/// (`protocol` can just be the next-header value).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlowKey {
+    pub src_addr: Vec<u8>,
+    pub dst_addr: Vec<u8>,
+    pub protocol: u8,
+    pub identification: u32,
+}
+
+/// A gap in the datagram being reassembled, inclusive of both ends, as in
+/// RFC 815.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hole {
+    first: usize,
+    last: usize,
+}
+
+/// In-progress reassembly of one datagram's fragments.
+struct Reassembly {
+    holes: Vec<Hole>,
+    buffer: Vec<u8>,
+    /// The `FragmentReassembler` generation this flow last received a
+    /// fragment at, used to evict flows that never complete.
+    last_touched: usize,
+}
+
+impl Reassembly {
+    fn new(generation: usize) -> Self {
+        Reassembly {
+            // A single hole spanning the entire datagram, per RFC 815;
+            // `usize::MAX` stands in for "end unknown until the final
+            // fragment (more_fragments == false) arrives and trims it".
+            holes: vec![Hole {
+                first: 0,
+                last: usize::MAX,
+            }],
+            buffer: Vec::new(),
+            last_touched: generation,
+        }
+    }
+}
+
+/// Reassembles IPv4/IPv6 fragments into complete datagrams using RFC 815's
+/// hole-descriptor algorithm.
+///
+/// Fragments are fed in via [`insert`](FragmentReassembler::insert), keyed by
+/// [`FlowKey`]. Each call advances this reassembler's internal generation
+/// counter and evicts any flow that hasn't received a fragment within
+/// `max_idle_generations` calls, so datagrams that never complete (e.g. a
+/// dropped final fragment) don't accumulate forever.
+pub struct FragmentReassembler {
+    flows: HashMap<FlowKey, Reassembly>,
+    generation: usize,
+    max_idle_generations: usize,
+}
+
+impl FragmentReassembler {
+    /// Creates a new, empty reassembler. A flow is evicted once
+    /// `max_idle_generations` other `insert` calls have happened since it
+    /// last received a fragment.
+    pub fn new(max_idle_generations: usize) -> Self {
+        FragmentReassembler {
+            flows: HashMap::new(),
+            generation: 0,
+            max_idle_generations,
+        }
+    }
+
+    /// Returns the number of datagrams currently being reassembled.
+    pub fn active_flow_count(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// Feeds one fragment into the reassembler.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Identifies which datagram this fragment belongs to.
+    /// * `fragment_offset` - The fragment's byte offset into the final
+    ///   datagram (the IPv4 Fragment Offset field, already multiplied by 8,
+    ///   or the IPv6 equivalent).
+    /// * `more_fragments` - Whether the IP header's more-fragments flag was
+    ///   set; `false` marks this as the datagram's final fragment.
+    /// * `data` - This fragment's payload bytes.
+    ///
+    /// # Returns
+    ///
+    /// `Some(Fragment)` wrapping the reassembled datagram once every hole has
+    /// been filled, consuming the flow's state; otherwise `None`, with the
+    /// partial reassembly kept around for further fragments.
+    ///
+    /// Overlapping fragments are resolved by trusting whichever bytes were
+    /// received first: a later fragment only ever fills gaps still present
+    /// in the hole list, never overwriting bytes already placed.
+    pub fn insert(
+        &mut self,
+        key: FlowKey,
+        fragment_offset: usize,
+        more_fragments: bool,
+        data: &[u8],
+    ) -> Option<Fragment> {
+        self.generation += 1;
+        self.evict_idle();
+
+        if data.is_empty() {
+            // No bytes to place; only relevant if it happens to complete an
+            // already fully-filled datagram (e.g. a duplicate final
+            // fragment), which `try_complete` below still checks for.
+            return self.try_complete(&key);
+        }
+
+        let frag_first = fragment_offset;
+        let frag_last = fragment_offset + data.len() - 1;
+
+        let reassembly = self
+            .flows
+            .entry(key.clone())
+            .or_insert_with(|| Reassembly::new(self.generation));
+        reassembly.last_touched = self.generation;
+
+        let mut i = 0;
+        while i < reassembly.holes.len() {
+            let hole = reassembly.holes[i];
+            if frag_first > hole.last || frag_last < hole.first {
+                i += 1;
+                continue;
+            }
+
+            reassembly.holes.remove(i);
+            let mut insert_at = i;
+
+            if frag_first > hole.first {
+                reassembly.holes.insert(
+                    insert_at,
+                    Hole {
+                        first: hole.first,
+                        last: frag_first - 1,
+                    },
+                );
+                insert_at += 1;
+            }
+            if frag_last < hole.last && more_fragments {
+                reassembly.holes.insert(
+                    insert_at,
+                    Hole {
+                        first: frag_last + 1,
+                        last: hole.last,
+                    },
+                );
+                insert_at += 1;
+            }
+
+            let copy_start = frag_first.max(hole.first);
+            let copy_end = frag_last.min(hole.last);
+            if copy_end >= copy_start {
+                if reassembly.buffer.len() <= copy_end {
+                    reassembly.buffer.resize(copy_end + 1, 0u8);
+                }
+                let src_start = copy_start - frag_first;
+                let src_end = copy_end - frag_first;
+                reassembly.buffer[copy_start..=copy_end]
+                    .copy_from_slice(&data[src_start..=src_end]);
+            }
+
+            i = insert_at;
+        }
+
+        self.try_complete(&key)
+    }
+
+    /// Removes and returns the given flow's completed datagram, if its hole
+    /// list is empty.
+    fn try_complete(&mut self, key: &FlowKey) -> Option<Fragment> {
+        let is_complete = self.flows.get(key).is_some_and(|r| r.holes.is_empty());
+        if !is_complete {
+            return None;
+        }
+
+        let reassembly = self.flows.remove(key)?;
+        Some(Fragment::new_from(Rc::from(reassembly.buffer)))
+    }
+
+    /// Drops any flow that hasn't received a fragment in the last
+    /// `max_idle_generations` calls to `insert`.
+    fn evict_idle(&mut self) {
+        let generation = self.generation;
+        let max_idle_generations = self.max_idle_generations;
+        self.flows
+            .retain(|_, r| generation - r.last_touched <= max_idle_generations);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtpacket::base::Layer;
+
+    fn key() -> FlowKey {
+        FlowKey {
+            src_addr: vec![10, 0, 0, 1],
+            dst_addr: vec![10, 0, 0, 2],
+            protocol: 17,
+            identification: 42,
+        }
+    }
+
+    #[test]
+    fn reassembles_two_in_order_fragments() {
+        let mut reassembler = FragmentReassembler::new(100);
+
+        assert!(reassembler
+            .insert(key(), 0, true, &[1, 2, 3, 4])
+            .is_none());
+        let completed = reassembler
+            .insert(key(), 4, false, &[5, 6])
+            .expect("second fragment should complete the datagram");
+
+        assert_eq!(
+            completed.layer_contents().unwrap().as_ref(),
+            &[1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(reassembler.active_flow_count(), 0);
+    }
+
+    #[test]
+    fn reassembles_out_of_order_fragments() {
+        let mut reassembler = FragmentReassembler::new(100);
+
+        assert!(reassembler.insert(key(), 4, false, &[5, 6]).is_none());
+        let completed = reassembler
+            .insert(key(), 0, true, &[1, 2, 3, 4])
+            .expect("completing the last hole should finish the datagram");
+
+        assert_eq!(
+            completed.layer_contents().unwrap().as_ref(),
+            &[1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn reassembles_three_fragments_with_a_gap_filled_last() {
+        let mut reassembler = FragmentReassembler::new(100);
+
+        assert!(reassembler.insert(key(), 0, true, &[1, 2]).is_none());
+        assert!(reassembler.insert(key(), 4, false, &[5, 6]).is_none());
+        let completed = reassembler
+            .insert(key(), 2, true, &[3, 4])
+            .expect("filling the middle gap should finish the datagram");
+
+        assert_eq!(
+            completed.layer_contents().unwrap().as_ref(),
+            &[1, 2, 3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn overlapping_fragment_keeps_first_received_bytes() {
+        let mut reassembler = FragmentReassembler::new(100);
+
+        assert!(reassembler.insert(key(), 0, true, &[1, 2, 3, 4]).is_none());
+        // Overlaps bytes 2..4 (already filled) and extends to 6.
+        let completed = reassembler
+            .insert(key(), 2, false, &[99, 99, 5, 6])
+            .expect("the overlapping fragment should complete the datagram");
+
+        assert_eq!(
+            completed.layer_contents().unwrap().as_ref(),
+            &[1, 2, 3, 4, 5, 6],
+            "bytes 2..4 should keep their first-received values"
+        );
+    }
+
+    #[test]
+    fn different_flows_reassemble_independently() {
+        let mut reassembler = FragmentReassembler::new(100);
+        let mut other = key();
+        other.identification = 99;
+
+        assert!(reassembler.insert(key(), 0, true, &[1, 2]).is_none());
+        assert!(reassembler.insert(other.clone(), 0, true, &[9, 9]).is_none());
+
+        assert_eq!(reassembler.active_flow_count(), 2);
+
+        let completed = reassembler
+            .insert(key(), 2, false, &[3, 4])
+            .expect("should complete only the first flow");
+        assert_eq!(completed.layer_contents().unwrap().as_ref(), &[1, 2, 3, 4]);
+        assert_eq!(reassembler.active_flow_count(), 1);
+    }
+
+    #[test]
+    fn idle_flows_are_evicted_and_do_not_leak() {
+        let mut reassembler = FragmentReassembler::new(2);
+
+        reassembler.insert(key(), 0, true, &[1, 2]);
+        assert_eq!(reassembler.active_flow_count(), 1);
+
+        let mut other = key();
+        other.identification = 1;
+        reassembler.insert(other.clone(), 0, true, &[1]);
+        other.identification = 2;
+        reassembler.insert(other.clone(), 0, true, &[1]);
+        other.identification = 3;
+        reassembler.insert(other, 0, true, &[1]);
+
+        // Three more `insert` calls have happened since `key()`'s fragment,
+        // exceeding `max_idle_generations` of 2, so it should be gone.
+        assert_eq!(reassembler.active_flow_count(), 3);
+    }
+}
@@ -0,0 +1,375 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, Index};
+use std::rc::Rc;
+
+use crate::rtpacket::base::fragment::Fragment;
+use crate::rtpacket::base::Layer;
+
+/// Width, in bytes, of the little-endian `u64` count field and each offset
+/// table entry.
+const ENTRY_WIDTH: usize = 8;
+
+/// An owned, flat buffer holding many fragments' bytes one after another,
+/// each individually addressable by index without re-parsing the whole
+/// buffer.
+///
+/// # Layout
+///
+/// * `count: u64` (little-endian) - the number of fragments.
+/// * `count` cumulative `u64` (little-endian) offsets - `offsets[i]` is the
+///   byte offset, within the data region, immediately past fragment `i`'s
+///   bytes. Fragment `i` therefore spans `offsets[i - 1]..offsets[i]` (with
+///   `offsets[-1]` taken as zero).
+/// * the concatenated fragment bytes.
+#[derive(Debug, Clone)]
+pub struct FragmentVector {
+    data: Vec<u8>,
+}
+
+impl FragmentVector {
+    /// A validating, borrowed view over this vector's bytes. Always
+    /// succeeds, since a `FragmentVector` only ever holds bytes it built
+    /// itself via [`FromIterator`].
+    fn view(&self) -> FragmentVectorView<'_> {
+        FragmentVectorView::new(&self.data)
+            .expect("a FragmentVector's own bytes are always a valid layout")
+    }
+
+    /// The number of fragments in this vector.
+    pub fn len(&self) -> usize {
+        self.view().len()
+    }
+
+    /// Whether this vector holds no fragments.
+    pub fn is_empty(&self) -> bool {
+        self.view().is_empty()
+    }
+
+    /// Returns the `index`th fragment's bytes, or `None` if `index` is out
+    /// of bounds.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.view().get(index)
+    }
+
+    /// Iterates over every fragment's bytes, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        (0..self.len()).map(move |i| &self.data[self.view().fragment_range(i)])
+    }
+}
+
+impl Deref for FragmentVector {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Index<usize> for FragmentVector {
+    type Output = [u8];
+
+    fn index(&self, index: usize) -> &[u8] {
+        &self.data[self.view().fragment_range(index)]
+    }
+}
+
+impl FromIterator<Fragment> for FragmentVector {
+    /// Builds a `FragmentVector` from an iterator of fragments, back-patching
+    /// the count and offset table once every fragment has been seen, since
+    /// neither is known until the whole iterator is consumed.
+    fn from_iter<I: IntoIterator<Item = Fragment>>(iter: I) -> Self {
+        // Reserve the count field up front with a placeholder; it's
+        // overwritten below once the real count is known.
+        let mut data = vec![0u8; ENTRY_WIDTH];
+        let mut offsets: Vec<u64> = Vec::new();
+        let mut payload: Vec<u8> = Vec::new();
+        let mut cumulative: u64 = 0;
+
+        for fragment in iter {
+            let bytes = fragment.layer_contents().unwrap_or_else(|| Rc::from(&[][..]));
+            cumulative += bytes.len() as u64;
+            offsets.push(cumulative);
+            payload.extend_from_slice(&bytes);
+        }
+
+        data[..ENTRY_WIDTH].copy_from_slice(&(offsets.len() as u64).to_le_bytes());
+        for offset in &offsets {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(&payload);
+
+        FragmentVector { data }
+    }
+}
+
+/// Errors returned by [`FragmentVectorView::new`] when `bytes` doesn't
+/// describe a valid [`FragmentVector`] layout.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FragmentVectorError {
+    /// `bytes` ends before the count field or the full offset table.
+    TooShort,
+    /// An offset table entry is smaller than the one before it.
+    NonMonotonicOffsets,
+    /// An offset table entry points past the end of the data region.
+    OffsetOutOfBounds,
+}
+
+impl fmt::Display for FragmentVectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FragmentVectorError::TooShort => {
+                write!(f, "buffer ends before the fragment vector's header")
+            }
+            FragmentVectorError::NonMonotonicOffsets => {
+                write!(f, "fragment vector offset table is not monotonic")
+            }
+            FragmentVectorError::OffsetOutOfBounds => {
+                write!(f, "fragment vector offset points past the end of the data region")
+            }
+        }
+    }
+}
+
+impl Error for FragmentVectorError {}
+
+/// A borrowed, validated view over a byte slice laid out as a
+/// [`FragmentVector`]. Unlike `FragmentVector` itself, which only ever holds
+/// bytes it built, this is meant for bytes coming from outside the process
+/// (e.g. read off the wire), so construction validates that the offset table
+/// is monotonic and every offset stays within the data region before
+/// indexing can ever read out of bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentVectorView<'a> {
+    bytes: &'a [u8],
+    count: usize,
+}
+
+impl<'a> FragmentVectorView<'a> {
+    /// Validates `bytes` as a `FragmentVector` layout and wraps it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FragmentVectorError`] if `bytes` is too short for its own
+    /// declared count, or if the offset table isn't monotonic and bounded by
+    /// the data region.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, FragmentVectorError> {
+        if bytes.len() < ENTRY_WIDTH {
+            return Err(FragmentVectorError::TooShort);
+        }
+        let count = u64::from_le_bytes(bytes[..ENTRY_WIDTH].try_into().unwrap()) as usize;
+
+        let header_len = count
+            .checked_mul(ENTRY_WIDTH)
+            .and_then(|offsets_len| offsets_len.checked_add(ENTRY_WIDTH))
+            .ok_or(FragmentVectorError::TooShort)?;
+        if bytes.len() < header_len {
+            return Err(FragmentVectorError::TooShort);
+        }
+        let data_len = (bytes.len() - header_len) as u64;
+
+        let mut previous = 0u64;
+        for i in 0..count {
+            let start = ENTRY_WIDTH + i * ENTRY_WIDTH;
+            let offset = u64::from_le_bytes(bytes[start..start + ENTRY_WIDTH].try_into().unwrap());
+            if offset < previous {
+                return Err(FragmentVectorError::NonMonotonicOffsets);
+            }
+            if offset > data_len {
+                return Err(FragmentVectorError::OffsetOutOfBounds);
+            }
+            previous = offset;
+        }
+
+        Ok(FragmentVectorView { bytes, count })
+    }
+
+    /// The number of fragments in this vector.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether this vector holds no fragments.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    fn offset_entry(&self, index: usize) -> u64 {
+        let start = ENTRY_WIDTH + index * ENTRY_WIDTH;
+        u64::from_le_bytes(self.bytes[start..start + ENTRY_WIDTH].try_into().unwrap())
+    }
+
+    fn header_len(&self) -> usize {
+        ENTRY_WIDTH + self.count * ENTRY_WIDTH
+    }
+
+    /// The byte range of the `index`th fragment within `self.bytes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, matching `Index::index`'s
+    /// contract.
+    fn fragment_range(&self, index: usize) -> std::ops::Range<usize> {
+        assert!(index < self.count, "fragment index out of bounds");
+        let data_start = self.header_len();
+        let start = if index == 0 {
+            0
+        } else {
+            self.offset_entry(index - 1) as usize
+        };
+        let end = self.offset_entry(index) as usize;
+        data_start + start..data_start + end
+    }
+
+    /// Returns the `index`th fragment's bytes, or `None` if `index` is out
+    /// of bounds.
+    pub fn get(&self, index: usize) -> Option<&'a [u8]> {
+        if index >= self.count {
+            return None;
+        }
+        let data_start = self.header_len();
+        let start = if index == 0 {
+            0
+        } else {
+            self.offset_entry(index - 1) as usize
+        };
+        let end = self.offset_entry(index) as usize;
+        Some(&self.bytes[data_start + start..data_start + end])
+    }
+
+    /// Iterates over every fragment's bytes, in order.
+    pub fn iter(&self) -> FragmentVectorViewIter<'a> {
+        FragmentVectorViewIter {
+            view: *self,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> Deref for FragmentVectorView<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl<'a> Index<usize> for FragmentVectorView<'a> {
+    type Output = [u8];
+
+    fn index(&self, index: usize) -> &[u8] {
+        &self.bytes[self.fragment_range(index)]
+    }
+}
+
+/// Iterator over a [`FragmentVectorView`]'s fragments, returned by
+/// [`FragmentVectorView::iter`].
+pub struct FragmentVectorViewIter<'a> {
+    view: FragmentVectorView<'a>,
+    index: usize,
+}
+
+impl<'a> Iterator for FragmentVectorViewIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.view.get(self.index)?;
+        self.index += 1;
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragments(items: &[&[u8]]) -> Vec<Fragment> {
+        items
+            .iter()
+            .map(|bytes| Fragment::new_from(Rc::from(*bytes)))
+            .collect()
+    }
+
+    #[test]
+    fn builds_and_indexes_fragments() {
+        let vector: FragmentVector = fragments(&[&[1, 2], &[], &[3, 4, 5]]).into_iter().collect();
+
+        assert_eq!(vector.len(), 3);
+        assert_eq!(&vector[0], &[1, 2]);
+        assert_eq!(&vector[1], &[] as &[u8]);
+        assert_eq!(&vector[2], &[3, 4, 5]);
+        assert_eq!(vector.get(3), None);
+    }
+
+    #[test]
+    fn iterates_in_order() {
+        let vector: FragmentVector = fragments(&[&[1], &[2, 2], &[3, 3, 3]]).into_iter().collect();
+        let collected: Vec<&[u8]> = vector.iter().collect();
+        assert_eq!(collected, vec![&[1u8][..], &[2, 2][..], &[3, 3, 3][..]]);
+    }
+
+    #[test]
+    fn empty_iterator_builds_an_empty_vector() {
+        let vector: FragmentVector = Vec::<Fragment>::new().into_iter().collect();
+        assert_eq!(vector.len(), 0);
+        assert!(vector.is_empty());
+    }
+
+    #[test]
+    fn view_round_trips_through_a_fragment_vectors_own_bytes() {
+        let vector: FragmentVector = fragments(&[&[1, 2], &[3, 4, 5]]).into_iter().collect();
+        let view = FragmentVectorView::new(&vector).unwrap();
+
+        assert_eq!(view.len(), 2);
+        assert_eq!(&view[0], &[1, 2]);
+        assert_eq!(&view[1], &[3, 4, 5]);
+        assert_eq!(view.iter().collect::<Vec<_>>(), vec![&[1u8, 2][..], &[3, 4, 5][..]]);
+    }
+
+    #[test]
+    fn view_rejects_buffer_too_short_for_its_own_count() {
+        // Declares 5 fragments but has no offset table or data at all.
+        let bytes = 5u64.to_le_bytes();
+        assert_eq!(
+            FragmentVectorView::new(&bytes),
+            Err(FragmentVectorError::TooShort)
+        );
+    }
+
+    #[test]
+    fn view_rejects_non_monotonic_offsets() {
+        let mut bytes = 2u64.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&5u64.to_le_bytes());
+        bytes.extend_from_slice(&3u64.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 5]);
+
+        assert_eq!(
+            FragmentVectorView::new(&bytes),
+            Err(FragmentVectorError::NonMonotonicOffsets)
+        );
+    }
+
+    #[test]
+    fn view_rejects_a_count_whose_header_len_would_overflow() {
+        // A crafted count large enough that `count * ENTRY_WIDTH` overflows
+        // `usize`, rather than panicking or wrapping to an undersized
+        // `header_len`.
+        let bytes = (usize::MAX as u64).to_le_bytes();
+        assert_eq!(
+            FragmentVectorView::new(&bytes),
+            Err(FragmentVectorError::TooShort)
+        );
+    }
+
+    #[test]
+    fn view_rejects_offset_past_the_data_region() {
+        let mut bytes = 1u64.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&100u64.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 4]);
+
+        assert_eq!(
+            FragmentVectorView::new(&bytes),
+            Err(FragmentVectorError::OffsetOutOfBounds)
+        );
+    }
+}
@@ -2,13 +2,18 @@ use std::error::Error;
 use std::rc::Rc;
 
 // Assumed external crate for lazy_static
-use crate::rtpacket::checksum::ChecksumVerificationResult;
+use crate::rtpacket::checksum::ChecksumResult;
 use crate::rtpacket::decode::{DecodeFeedback, LayerType};
-use crate::rtpacket::error::decodererror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::{DecodeError, VerifyChecksumError};
 use crate::rtpacket::layerclass::LayerClass;
+use crate::rtpacket::layertype::LayerTypeID;
+use crate::rtpacket::writer::SerializableLayer;
 
+pub(crate) mod decompressedlayer;
+pub(crate) mod define_layer;
 pub(crate) mod fragment;
 pub(crate) mod payload;
+pub(crate) mod quic;
 
 // Common trait for all layers, providing basic methods.
 pub trait Layer {
@@ -21,14 +26,89 @@ pub trait Layer {
     // Returns the payload within this layer.
     fn layer_payload(&self) -> Option<Rc<[u8]>>;
     fn string(&self) -> String;
+
+    /// Returns this layer as a `SerializableLayer`, if it supports being
+    /// re-encoded back into bytes. Layers that only support decoding (e.g.
+    /// `DecodeFailure`) return `None`.
+    fn as_serializable(&self) -> Option<Rc<dyn SerializableLayer>> {
+        None
+    }
+
+    /// Serializes this layer's decoded fields into `serializer`, so a whole
+    /// `Packet` can be exported as JSON (or any other `serde` format) instead
+    /// of only the human-readable `string()`/`dump()` text.
+    ///
+    /// `Layer` is a trait object (`Rc<dyn Layer>`), so it can't have a
+    /// generic `serde::Serialize::serialize<S: Serializer>` method directly.
+    /// Implementations instead forward into the given `erased_serde`
+    /// serializer, typically via `erased_serde::serialize(&some_local_struct, serializer)`.
+    fn serialize_fields(
+        &self,
+        serializer: &mut dyn erased_serde::Serializer,
+    ) -> Result<(), erased_serde::Error>;
+
+    /// Returns this layer as a `ChecksumVerifiable`, if it carries a checksum
+    /// that can be verified after decoding. Layers without a checksum (e.g.
+    /// `Payload`) return `None`.
+    fn as_checksum_verifiable(&self) -> Option<Rc<dyn ChecksumVerifiable>> {
+        None
+    }
+
+    /// The `LayerTypeID` this layer's payload should be decoded as next, if
+    /// this layer knows (i.e. it implements `Payloadable`). Returns `None`
+    /// for layers with no further payload to decode (e.g. `DecodeFailure`).
+    ///
+    /// `Payloadable` itself can't be used as a trait object (its
+    /// `can_decode` returns `impl LayerClass`), so `Rc<dyn Layer>` can't be
+    /// downcast to `dyn Payloadable` to read `next_layer_type` directly;
+    /// implementations instead forward their own `Payloadable::next_layer_type`
+    /// here. Used by `PacketBuilder::next_decoder_auto` to self-dispatch via
+    /// the global decoder registry in `crate::rtpacket::layertype`.
+    fn next_layer_type_id(&self) -> Option<LayerTypeID> {
+        None
+    }
 }
 
-/// Trait for layers that contain a checksum which can be verified after
-/// a packet has been decoded.
-pub trait LayerWithChecksum {
-    /// Verifies the checksum and returns the result as a `Result` type,
-    /// encapsulating `ChecksumVerificationResult` on success, or an error message on failure.
-    fn verify_checksum(&self) -> Result<ChecksumVerificationResult, Box<dyn Error>>;
+/// Lets `dyn Layer` itself be passed anywhere an `erased_serde::Serialize`
+/// is expected, by forwarding straight into `serialize_fields`.
+impl erased_serde::Serialize for dyn Layer {
+    fn erased_serialize(
+        &self,
+        serializer: &mut dyn erased_serde::Serializer,
+    ) -> Result<(), erased_serde::Error> {
+        self.serialize_fields(serializer)
+    }
+}
+
+/// Lets any `dyn Layer` be used as a plain `serde::Serialize` value (e.g. as
+/// an element of a `Vec<&dyn Layer>`), bridging back from the object-safe
+/// `serialize_fields` to a concrete `Serializer`. `erased_serde::serialize`
+/// (not the inherent `Serializer::erase`) is what lets this recover the
+/// concrete `S::Ok`/`S::Error` that `serialize_fields` alone can't produce.
+impl serde::Serialize for dyn Layer {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        erased_serde::serialize(self, serializer)
+    }
+}
+
+/// Trait for layers that carry a checksum which can be verified after a
+/// packet has been decoded, e.g. TCP/UDP transport layers or IPv4's header
+/// checksum.
+///
+/// A transport-layer checksum also covers a pseudo-header drawn from the
+/// network layer (source/destination address, protocol, segment length).
+/// Since `verify_checksum` takes no `Packet` argument, an implementation that
+/// needs pseudo-header context is expected to capture it (e.g. the resolved
+/// addresses, or a handle back to the owning network layer) at decode time,
+/// rather than have it threaded through this call.
+pub trait ChecksumVerifiable {
+    /// Verifies the checksum and returns the comparison result, or an error
+    /// if verification itself could not be completed (as opposed to the
+    /// checksum simply being wrong, which is reported via `ChecksumResult::valid`).
+    fn verify_checksum(&self) -> Result<ChecksumResult, VerifyChecksumError>;
 }
 
 // Trait for layers that contain a payload.
@@ -40,7 +120,7 @@ pub trait Payloadable: Layer {
     fn decode_from_bytes(
         &mut self,
         data: Rc<[u8]>,
-        _df: Box<dyn DecodeFeedback>,
+        _df: Rc<dyn DecodeFeedback>,
     ) -> Result<(), DecodeError>;
 }
 
@@ -3,12 +3,11 @@ use std::io;
 use std::ops::Deref;
 use std::rc::Rc;
 
+use erased_serde::Serialize as _;
+
 use crate::rtpacket::base::{ApplicationLayer, Layer, Payloadable};
-use crate::rtpacket::checksum::ChecksumVerificationResult;
 use crate::rtpacket::decode::{DecodeFeedback, decoder_builder, LayerType};
-use crate::rtpacket::error::{decodeerror, PacketError};
-use crate::rtpacket::error::decodeerror::DecodeError;
-use crate::rtpacket::error::ErrorDecodeable;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
 use crate::rtpacket::layerclass::LayerClass;
 use crate::rtpacket::layertype::LayerTypeID;
 use crate::rtpacket::layertype::LayerTypes::{
@@ -62,20 +61,37 @@ impl Layer for Payload {
         None
     }
 
-    fn verify_checksum(&self) -> Result<ChecksumVerificationResult, PacketError> {
-        Err(PacketError::try_from(decodeerror::DecodeError::new(
-            "Payload layer does not have a checksum",
-            None,
-        ))
-        .unwrap())
-    }
-
     fn string(&self) -> String {
         match &self.in_data {
             None => "0 byte(s)".to_string(),
             Some(data) => format!("{} byte(s)", data.deref().len()),
         }
     }
+
+    fn as_serializable(&self) -> Option<Rc<dyn SerializableLayer>> {
+        Some(Rc::new(self.clone()))
+    }
+
+    fn serialize_fields(
+        &self,
+        serializer: &mut dyn erased_serde::Serializer,
+    ) -> Result<(), erased_serde::Error> {
+        #[derive(serde::Serialize)]
+        struct Fields<'a> {
+            layer_type: &'a str,
+            contents_hex: Option<String>,
+        }
+
+        Fields {
+            layer_type: &self.layer_type.name,
+            contents_hex: self.in_data.as_deref().map(hex::encode),
+        }
+        .erased_serialize(serializer)
+    }
+
+    fn next_layer_type_id(&self) -> Option<LayerTypeID> {
+        Some(Payloadable::next_layer_type(self).id)
+    }
 }
 
 impl ApplicationLayer for Payload {
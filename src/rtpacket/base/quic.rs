@@ -0,0 +1,194 @@
+use std::rc::Rc;
+
+use erased_serde::Serialize as _;
+
+use crate::rtpacket::base::Layer;
+use crate::rtpacket::decode::decoder_builder;
+use crate::rtpacket::layertype::LayerType;
+use crate::rtpacket::layertype::LayerTypeID;
+use crate::rtpacket::layertype::LayerTypes::{LayerTypePayload, LayerTypeQUIC};
+
+/// The semantic meaning of a QUIC long header's 2-bit packet-type field
+/// (RFC 9000 section 17.2), plus `VersionNegotiation` for the one long
+/// header [`crate::rtpacket::decode::decodequic::decode_quic`] recognizes by
+/// its all-zero version rather than this field, whose bits carry no defined
+/// meaning on a Version Negotiation packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuicPacketType {
+    VersionNegotiation,
+    Initial,
+    ZeroRtt,
+    Handshake,
+    Retry,
+}
+
+/// The structurally-decoded fields of a QUIC packet header (RFC 9000
+/// section 17), the portion readable without the connection's keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuicHeader {
+    /// A long header, exchanged before a connection's 1-RTT keys are
+    /// available (Initial, 0-RTT, Handshake, Retry, or Version Negotiation).
+    Long {
+        fixed_bit: bool,
+        packet_type: QuicPacketType,
+        version: u32,
+        dest_connection_id: Rc<[u8]>,
+        source_connection_id: Rc<[u8]>,
+    },
+    /// A short header (1-RTT). Unlike a long header, the wire format carries
+    /// no length for `dest_connection_id` of its own — the caller supplies
+    /// the length negotiated for the connection via
+    /// `DecodeOptions::quic_short_header_dcid_len`.
+    Short {
+        fixed_bit: bool,
+        spin_bit: bool,
+        key_phase: bool,
+        dest_connection_id: Rc<[u8]>,
+    },
+}
+
+/// A layer holding a QUIC packet's structurally-decoded header, see
+/// [`QuicHeader`].
+///
+/// Only parses what's readable before a packet's connection-specific keys
+/// are available: header form, version, connection IDs, and the handful of
+/// flag bits neqo's own structural parse reads prior to decryption.
+/// Everything past the header is still protected, so
+/// `decode_quic` records it as a plain
+/// [`Payload`](crate::rtpacket::base::payload::Payload) layer (and the
+/// packet's application layer) directly, rather than through this layer's
+/// `layer_payload`, which stays `None` — there's no further structural
+/// decoding to defer to without the connection's keys.
+#[derive(Clone)]
+pub struct QuicLayer {
+    layer_type: LayerType,
+    header_bytes: Rc<[u8]>,
+    header: QuicHeader,
+}
+
+impl QuicLayer {
+    pub(crate) fn new(header_bytes: Rc<[u8]>, header: QuicHeader) -> Self {
+        QuicLayer {
+            layer_type: LayerType {
+                id: LayerTypeQUIC as LayerTypeID,
+                name: "QUIC".to_owned(),
+                decoder: decoder_builder(LayerTypeQUIC),
+            },
+            header_bytes,
+            header,
+        }
+    }
+
+    /// The structurally-decoded header fields.
+    pub fn header(&self) -> &QuicHeader {
+        &self.header
+    }
+}
+
+impl Layer for QuicLayer {
+    fn layer_type(&self) -> LayerType {
+        self.layer_type.clone()
+    }
+
+    fn layer_contents(&self) -> Option<Rc<[u8]>> {
+        Some(self.header_bytes.clone())
+    }
+
+    fn layer_payload(&self) -> Option<Rc<[u8]>> {
+        None
+    }
+
+    fn string(&self) -> String {
+        match &self.header {
+            QuicHeader::Long {
+                packet_type,
+                version,
+                ..
+            } => format!("QUIC long header, {:?}, version {:#010x}", packet_type, version),
+            QuicHeader::Short {
+                spin_bit,
+                key_phase,
+                ..
+            } => format!(
+                "QUIC short header, spin_bit={}, key_phase={}",
+                spin_bit, key_phase
+            ),
+        }
+    }
+
+    fn serialize_fields(
+        &self,
+        serializer: &mut dyn erased_serde::Serializer,
+    ) -> Result<(), erased_serde::Error> {
+        #[derive(serde::Serialize)]
+        #[serde(tag = "header_form")]
+        enum Fields {
+            Long {
+                fixed_bit: bool,
+                packet_type: String,
+                version: u32,
+                dest_connection_id_hex: String,
+                source_connection_id_hex: String,
+            },
+            Short {
+                fixed_bit: bool,
+                spin_bit: bool,
+                key_phase: bool,
+                dest_connection_id_hex: String,
+            },
+        }
+
+        let fields = match &self.header {
+            QuicHeader::Long {
+                fixed_bit,
+                packet_type,
+                version,
+                dest_connection_id,
+                source_connection_id,
+            } => Fields::Long {
+                fixed_bit: *fixed_bit,
+                packet_type: format!("{:?}", packet_type),
+                version: *version,
+                dest_connection_id_hex: hex::encode(dest_connection_id),
+                source_connection_id_hex: hex::encode(source_connection_id),
+            },
+            QuicHeader::Short {
+                fixed_bit,
+                spin_bit,
+                key_phase,
+                dest_connection_id,
+            } => Fields::Short {
+                fixed_bit: *fixed_bit,
+                spin_bit: *spin_bit,
+                key_phase: *key_phase,
+                dest_connection_id_hex: hex::encode(dest_connection_id),
+            },
+        };
+
+        fields.erased_serialize(serializer)
+    }
+
+    fn next_layer_type_id(&self) -> Option<LayerTypeID> {
+        Some(LayerTypePayload as LayerTypeID)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_returns_the_constructed_fields() {
+        let header = QuicHeader::Short {
+            fixed_bit: true,
+            spin_bit: false,
+            key_phase: true,
+            dest_connection_id: Rc::from(&[1, 2, 3, 4][..]),
+        };
+        let layer = QuicLayer::new(Rc::from(&[0x40][..]), header.clone());
+
+        assert_eq!(layer.header(), &header);
+        assert_eq!(layer.layer_contents().as_deref(), Some(&[0x40][..]));
+        assert!(layer.layer_payload().is_none());
+    }
+}
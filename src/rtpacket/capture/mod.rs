@@ -2,8 +2,15 @@ use std::any::Any;
 use std::rc::Rc;
 use std::time::SystemTime;
 
+pub mod pcap;
+
 pub trait AnyClone: Any {
     fn clone_box(&self) -> Box<dyn AnyClone>;
+
+    /// Returns this value as a `&dyn Any`, so a concrete ancillary-data type
+    /// can be recovered via `Any::downcast_ref`. Backs
+    /// `CaptureInfo::ancillary`/`ancillary_iter`.
+    fn as_any(&self) -> &dyn Any;
 }
 
 fn clone_any_clone_box(item: &Box<dyn AnyClone>) -> Box<dyn AnyClone> {
@@ -37,6 +44,10 @@ macro_rules! define_capture_info_struct {
             fn clone_box(&self) -> Box<dyn AnyClone> {
                 Box::new(self.clone())
             }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
         }
 
         impl Clone for $name {
@@ -78,6 +89,21 @@ impl CaptureInfo {
     pub fn add_ancillary_data(&mut self, data: Box<dyn AnyClone>) {
         self.ancillary_data.push(data);
     }
+
+    /// Returns the first stored ancillary-data entry of concrete type `T`,
+    /// if any — e.g. a VLAN tag or RSS hash a packet source stashed via
+    /// `add_ancillary_data`.
+    pub fn ancillary<T: 'static>(&self) -> Option<&T> {
+        self.ancillary_iter::<T>().next()
+    }
+
+    /// Iterates over every stored ancillary-data entry of concrete type `T`,
+    /// in the order they were added, skipping entries of any other type.
+    pub fn ancillary_iter<T: 'static>(&self) -> impl Iterator<Item = &T> {
+        self.ancillary_data
+            .iter()
+            .filter_map(|entry| entry.as_any().downcast_ref::<T>())
+    }
 }
 
 // Contains metadata for a packet, including capture information and
@@ -108,3 +134,139 @@ pub struct PacketMetadata {
     /// packet formation or due to partial capture of the packet data.
     pub truncated: bool,
 }
+
+/// Serializes the metadata as `{ timestamp, capture_length, length,
+/// interface_index, truncated }`, with `timestamp` rendered as an RFC 3339
+/// UTC string. `ancillary_data` is omitted since `AnyClone` trait objects
+/// aren't serializable.
+impl serde::Serialize for PacketMetadata {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("PacketMetadata", 5)?;
+        state.serialize_field("timestamp", &rfc3339(self.timestamp))?;
+        state.serialize_field("capture_length", &self.capture_length)?;
+        state.serialize_field("length", &self.length)?;
+        state.serialize_field("interface_index", &self.interface_index)?;
+        state.serialize_field("truncated", &self.truncated)?;
+        state.end()
+    }
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp truncated to whole seconds,
+/// e.g. `"2024-01-02T03:04:05Z"`. Times before the Unix epoch are clamped to
+/// it.
+pub(crate) fn rfc3339(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+
+    let days = secs.div_euclid(86400);
+    let time_of_day = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    let hour = time_of_day / 3600;
+    let minute = (time_of_day % 3600) / 60;
+    let second = time_of_day % 60;
+
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
+/// Converts a day count since the Unix epoch (1970-01-01) into a proleptic
+/// Gregorian (year, month, day), using Howard Hinnant's `civil_from_days`
+/// algorithm.
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097); // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // [0, 399]
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32; // [1, 31]
+    let m = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32; // [1, 12]
+    let y = if m <= 2 { y + 1 } else { y };
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct VlanTag(u16);
+
+    impl AnyClone for VlanTag {
+        fn clone_box(&self) -> Box<dyn AnyClone> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct RssHash(u32);
+
+    impl AnyClone for RssHash {
+        fn clone_box(&self) -> Box<dyn AnyClone> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn capture_info_with(ancillary_data: Vec<Box<dyn AnyClone>>) -> CaptureInfo {
+        CaptureInfo {
+            timestamp: SystemTime::UNIX_EPOCH,
+            capture_length: 0,
+            length: 0,
+            interface_index: 0,
+            ancillary_data,
+        }
+    }
+
+    #[test]
+    fn ancillary_recovers_a_stored_value_by_type() {
+        let info = capture_info_with(vec![Box::new(VlanTag(42))]);
+
+        assert_eq!(info.ancillary::<VlanTag>(), Some(&VlanTag(42)));
+        assert!(info.ancillary::<RssHash>().is_none());
+    }
+
+    #[test]
+    fn ancillary_iter_yields_every_matching_entry_in_order() {
+        let info = capture_info_with(vec![
+            Box::new(VlanTag(1)),
+            Box::new(RssHash(99)),
+            Box::new(VlanTag(2)),
+        ]);
+
+        let tags: Vec<&VlanTag> = info.ancillary_iter::<VlanTag>().collect();
+        assert_eq!(tags, vec![&VlanTag(1), &VlanTag(2)]);
+    }
+
+    #[test]
+    fn ancillary_iter_is_empty_when_nothing_matches() {
+        let info = capture_info_with(vec![Box::new(RssHash(7))]);
+        assert_eq!(info.ancillary_iter::<VlanTag>().count(), 0);
+    }
+
+    #[test]
+    fn rfc3339_formats_the_unix_epoch() {
+        assert_eq!(rfc3339(std::time::UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn rfc3339_formats_a_known_timestamp() {
+        // 2024-01-02T03:04:05Z
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1704164645);
+        assert_eq!(rfc3339(time), "2024-01-02T03:04:05Z");
+    }
+}
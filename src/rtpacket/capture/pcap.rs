@@ -0,0 +1,355 @@
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::rtpacket::capture::PacketMetadata;
+
+/// Byte length of the file-level header every pcap file opens with.
+const GLOBAL_HEADER_LEN: usize = 24;
+
+/// Byte length of the per-packet header preceding each record's captured
+/// bytes.
+const RECORD_HEADER_LEN: usize = 16;
+
+/// Global header magic for microsecond-resolution timestamps, read in the
+/// file's own byte order.
+const MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+
+/// Global header magic for nanosecond-resolution timestamps (the `nsec`
+/// variant some newer writers emit), read in the file's own byte order.
+const MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+
+/// Largest `incl_len` a record header is allowed to claim, rejected before
+/// it's used to size an allocation. Generous enough for any jumbo-frame
+/// capture, but well short of a corrupted or hostile header forcing a
+/// multi-gigabyte allocation (mirrors the same guard in
+/// [`crate::rtpacket::packet::streamdecoder::Decoder`]).
+const MAX_CAPTURE_LENGTH: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteOrder {
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeResolution {
+    Micros,
+    Nanos,
+}
+
+fn read_u32(bytes: &[u8], byte_order: ByteOrder) -> u32 {
+    match byte_order {
+        ByteOrder::Little => u32::from_le_bytes(bytes.try_into().unwrap()),
+        ByteOrder::Big => u32::from_be_bytes(bytes.try_into().unwrap()),
+    }
+}
+
+/// Reads packets out of a classic libpcap (`.pcap`) capture file, the format
+/// written by `tcpdump -w` and read by Wireshark, as opposed to the
+/// invented framing [`crate::rtpacket::packet::streamdecoder::Decoder`]
+/// expects from a tailed capture socket.
+///
+/// Records are yielded as `(PacketMetadata, Rc<[u8]>)` rather than
+/// `CaptureInfo`: unlike `CaptureInfo`, `PacketMetadata` already carries a
+/// `truncated` flag, which is exactly what a record whose `incl_len` is
+/// less than its `orig_len` needs to report.
+pub struct PcapReader<R: Read> {
+    reader: R,
+    byte_order: ByteOrder,
+    time_resolution: TimeResolution,
+    snaplen: u32,
+    linktype: u32,
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Parses the 24-byte global header off the front of `reader`, detecting
+    /// the microsecond/nanosecond magic and the byte order it was written
+    /// in, and returns a reader positioned at the first packet record.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; GLOBAL_HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        let magic_le = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let magic_be = u32::from_be_bytes(header[0..4].try_into().unwrap());
+        let (byte_order, time_resolution) = match (magic_le, magic_be) {
+            (MAGIC_MICROS, _) => (ByteOrder::Little, TimeResolution::Micros),
+            (MAGIC_NANOS, _) => (ByteOrder::Little, TimeResolution::Nanos),
+            (_, MAGIC_MICROS) => (ByteOrder::Big, TimeResolution::Micros),
+            (_, MAGIC_NANOS) => (ByteOrder::Big, TimeResolution::Nanos),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("not a pcap file: unrecognized global header magic 0x{magic_le:08x}"),
+                ))
+            }
+        };
+
+        let snaplen = read_u32(&header[16..20], byte_order);
+        let linktype = read_u32(&header[20..24], byte_order);
+
+        Ok(PcapReader {
+            reader,
+            byte_order,
+            time_resolution,
+            snaplen,
+            linktype,
+        })
+    }
+
+    /// The `DLT_*`-style link-layer type declared in the file's global
+    /// header, identifying how the first layer of every record should be
+    /// decoded (see `crate::rtpacket::encap::EncapType`).
+    pub fn linktype(&self) -> u32 {
+        self.linktype
+    }
+
+    /// The maximum per-packet capture length declared in the file's global
+    /// header.
+    pub fn snaplen(&self) -> u32 {
+        self.snaplen
+    }
+
+    /// Reads and returns the next packet record, or `Ok(None)` at a clean
+    /// end of file right at a record boundary.
+    pub fn next_record(&mut self) -> io::Result<Option<(PacketMetadata, Rc<[u8]>)>> {
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        if !read_exact_or_eof(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+
+        let ts_sec = read_u32(&header[0..4], self.byte_order);
+        let ts_frac = read_u32(&header[4..8], self.byte_order);
+        let incl_len = read_u32(&header[8..12], self.byte_order);
+        let orig_len = read_u32(&header[12..16], self.byte_order);
+
+        if incl_len as usize > MAX_CAPTURE_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "record header claims a capture length of {incl_len} bytes, \
+                     which exceeds the {MAX_CAPTURE_LENGTH} byte limit"
+                ),
+            ));
+        }
+
+        let mut body = vec![0u8; incl_len as usize];
+        self.reader.read_exact(&mut body)?;
+
+        let timestamp = UNIX_EPOCH
+            + Duration::from_secs(ts_sec as u64)
+            + match self.time_resolution {
+                TimeResolution::Micros => Duration::from_micros(ts_frac as u64),
+                TimeResolution::Nanos => Duration::from_nanos(ts_frac as u64),
+            };
+
+        let metadata = PacketMetadata {
+            timestamp,
+            capture_length: incl_len as usize,
+            length: orig_len as usize,
+            interface_index: 0,
+            ancillary_data: Vec::new(),
+            truncated: incl_len < orig_len,
+        };
+
+        Ok(Some((metadata, Rc::from(body))))
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = io::Result<(PacketMetadata, Rc<[u8]>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Like `Read::read_exact`, but reports a clean `Ok(false)` instead of an
+/// error when the reader is already at its end and nothing has been read
+/// yet. A reader that closes partway through `buf` is still an error.
+fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => {
+                if filled == 0 {
+                    return Ok(false);
+                }
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "pcap file closed mid-record"));
+            }
+            Ok(n) => filled += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+/// Writes packets to a classic libpcap (`.pcap`) capture file, in the host's
+/// native byte order and with microsecond-resolution timestamps — the
+/// original pcap variant, readable by the widest range of tools.
+pub struct PcapWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Writes the 24-byte global header (microsecond magic, version 2.4,
+    /// the given `snaplen`/`linktype`) and returns a writer ready to accept
+    /// packet records via [`write_packet`](Self::write_packet).
+    pub fn new(mut writer: W, snaplen: u32, linktype: u32) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(GLOBAL_HEADER_LEN);
+        header.extend_from_slice(&MAGIC_MICROS.to_ne_bytes());
+        header.extend_from_slice(&2u16.to_ne_bytes());
+        header.extend_from_slice(&4u16.to_ne_bytes());
+        header.extend_from_slice(&0i32.to_ne_bytes());
+        header.extend_from_slice(&0u32.to_ne_bytes());
+        header.extend_from_slice(&snaplen.to_ne_bytes());
+        header.extend_from_slice(&linktype.to_ne_bytes());
+        writer.write_all(&header)?;
+
+        Ok(PcapWriter { writer })
+    }
+
+    /// Writes one packet record: a 16-byte header derived from `metadata`
+    /// followed by `data` verbatim.
+    ///
+    /// `metadata.capture_length` is expected to equal `data.len()`; the
+    /// written `incl_len` is `data.len()` regardless, so a caller that lets
+    /// the two drift only loses metadata accuracy, not framing.
+    pub fn write_packet(&mut self, metadata: &PacketMetadata, data: &[u8]) -> io::Result<()> {
+        let since_epoch = metadata.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        let mut header = Vec::with_capacity(RECORD_HEADER_LEN);
+        header.extend_from_slice(&(since_epoch.as_secs() as u32).to_ne_bytes());
+        header.extend_from_slice(&since_epoch.subsec_micros().to_ne_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_ne_bytes());
+        header.extend_from_slice(&(metadata.length as u32).to_ne_bytes());
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn metadata(timestamp: std::time::SystemTime, capture_length: usize, length: usize) -> PacketMetadata {
+        PacketMetadata {
+            timestamp,
+            capture_length,
+            length,
+            interface_index: 0,
+            ancillary_data: Vec::new(),
+            truncated: capture_length < length,
+        }
+    }
+
+    #[test]
+    fn writer_then_reader_round_trips_a_packet() {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = PcapWriter::new(&mut bytes, 65535, 1).unwrap();
+            let info = metadata(UNIX_EPOCH + Duration::from_secs(1_704_164_645), 4, 4);
+            writer.write_packet(&info, &[1, 2, 3, 4]).unwrap();
+        }
+
+        let mut reader = PcapReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.snaplen(), 65535);
+        assert_eq!(reader.linktype(), 1);
+
+        let (info, data) = reader.next_record().unwrap().expect("one record should be present");
+        assert_eq!(data.as_ref(), &[1, 2, 3, 4]);
+        assert_eq!(info.capture_length, 4);
+        assert_eq!(info.length, 4);
+        assert!(!info.truncated);
+        assert_eq!(info.timestamp, UNIX_EPOCH + Duration::from_secs(1_704_164_645));
+
+        assert!(reader.next_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn reader_marks_a_record_truncated_when_incl_len_is_less_than_orig_len() {
+        let mut global_header = Vec::new();
+        global_header.extend_from_slice(&MAGIC_MICROS.to_le_bytes());
+        global_header.extend_from_slice(&2u16.to_le_bytes());
+        global_header.extend_from_slice(&4u16.to_le_bytes());
+        global_header.extend_from_slice(&0i32.to_le_bytes());
+        global_header.extend_from_slice(&0u32.to_le_bytes());
+        global_header.extend_from_slice(&65535u32.to_le_bytes());
+        global_header.extend_from_slice(&1u32.to_le_bytes());
+
+        let mut record = Vec::new();
+        record.extend_from_slice(&0u32.to_le_bytes());
+        record.extend_from_slice(&0u32.to_le_bytes());
+        record.extend_from_slice(&2u32.to_le_bytes()); // incl_len
+        record.extend_from_slice(&4u32.to_le_bytes()); // orig_len
+        record.extend_from_slice(&[1, 2]);
+
+        global_header.extend_from_slice(&record);
+
+        let mut reader = PcapReader::new(Cursor::new(global_header)).unwrap();
+        let (info, data) = reader.next_record().unwrap().expect("one record should be present");
+        assert_eq!(data.as_ref(), &[1, 2]);
+        assert_eq!(info.length, 4);
+        assert!(info.truncated);
+    }
+
+    #[test]
+    fn reader_handles_a_byte_swapped_nanosecond_header() {
+        let mut global_header = Vec::new();
+        global_header.extend_from_slice(&MAGIC_NANOS.to_be_bytes());
+        global_header.extend_from_slice(&2u16.to_be_bytes());
+        global_header.extend_from_slice(&4u16.to_be_bytes());
+        global_header.extend_from_slice(&0i32.to_be_bytes());
+        global_header.extend_from_slice(&0u32.to_be_bytes());
+        global_header.extend_from_slice(&65535u32.to_be_bytes());
+        global_header.extend_from_slice(&147u32.to_be_bytes());
+
+        let mut record = Vec::new();
+        record.extend_from_slice(&1u32.to_be_bytes()); // ts_sec
+        record.extend_from_slice(&500_000_000u32.to_be_bytes()); // ts_nsec
+        record.extend_from_slice(&3u32.to_be_bytes());
+        record.extend_from_slice(&3u32.to_be_bytes());
+        record.extend_from_slice(&[9, 9, 9]);
+
+        global_header.extend_from_slice(&record);
+
+        let mut reader = PcapReader::new(Cursor::new(global_header)).unwrap();
+        assert_eq!(reader.linktype(), 147);
+
+        let (info, data) = reader.next_record().unwrap().expect("one record should be present");
+        assert_eq!(data.as_ref(), &[9, 9, 9]);
+        assert_eq!(info.timestamp, UNIX_EPOCH + Duration::from_secs(1) + Duration::from_millis(500));
+    }
+
+    #[test]
+    fn new_rejects_a_header_with_unrecognized_magic() {
+        let bytes = vec![0u8; GLOBAL_HEADER_LEN];
+        assert!(PcapReader::new(Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn next_record_rejects_a_header_claiming_an_oversized_capture_length() {
+        let mut global_header = Vec::new();
+        global_header.extend_from_slice(&MAGIC_MICROS.to_le_bytes());
+        global_header.extend_from_slice(&2u16.to_le_bytes());
+        global_header.extend_from_slice(&4u16.to_le_bytes());
+        global_header.extend_from_slice(&0i32.to_le_bytes());
+        global_header.extend_from_slice(&0u32.to_le_bytes());
+        global_header.extend_from_slice(&65535u32.to_le_bytes());
+        global_header.extend_from_slice(&1u32.to_le_bytes());
+
+        global_header.extend_from_slice(&0u32.to_le_bytes());
+        global_header.extend_from_slice(&0u32.to_le_bytes());
+        global_header.extend_from_slice(&(MAX_CAPTURE_LENGTH as u32 + 1).to_le_bytes());
+        global_header.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut reader = PcapReader::new(Cursor::new(global_header)).unwrap();
+        reader
+            .next_record()
+            .expect_err("a capture length over MAX_CAPTURE_LENGTH should be rejected before allocating");
+    }
+}
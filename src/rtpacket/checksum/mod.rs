@@ -1,24 +1,26 @@
+use std::rc::Rc;
+
 use crate::rtpacket::base::Layer;
 
 /// Represents the outcome of checksum verification, including both successful
 /// and unsuccessful verifications.
 #[derive(Debug, Clone, Copy)]
-pub struct ChecksumVerificationResult {
+pub struct ChecksumResult {
     /// Indicates whether the checksum verification was successful.
     pub valid: bool,
-    /// The correct checksum that was expected.
-    pub correct: u32,
-    /// The actual checksum found, which may be incorrect.
+    /// The checksum actually found in the layer's contents.
     pub actual: u32,
+    /// The checksum that was expected, computed from the layer (and, for
+    /// transport layers, pseudo-header) bytes.
+    pub expected: u32,
 }
 
 /// Provides detailed information about a failed checksum verification for a layer.
 pub struct ChecksumMismatch {
     /// The checksum verification result that failed.
-    pub result: ChecksumVerificationResult,
+    pub result: ChecksumResult,
     /// The layer whose checksum verification failed.
-    // Assuming `Layer` is a trait defined elsewhere.
-    pub layer: Box<dyn Layer>,
+    pub layer: Rc<dyn Layer>,
     /// The index of the layer within the packet.
     pub layer_index: usize,
 }
@@ -53,6 +55,148 @@ pub fn fold_checksum(csum: u32) -> u16 {
     !(csum as u16)
 }
 
+/// Incrementally accumulates an RFC 1071 Internet checksum across one or more
+/// calls to `add_bytes`, so a layer's header and payload can be folded in
+/// without first concatenating them into a single buffer.
+///
+/// `Checksum` carries an odd trailing byte from one `add_bytes` call over to
+/// the next, so `checksum.add_bytes(a); checksum.add_bytes(b);` always
+/// produces the same result as a single `add_bytes` call over `a` followed by
+/// `b`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checksum {
+    sum: u32,
+    trailing_byte: Option<u8>,
+}
+
+impl Checksum {
+    /// Creates a new, empty `Checksum` accumulator.
+    pub fn new() -> Self {
+        Checksum {
+            sum: 0,
+            trailing_byte: None,
+        }
+    }
+
+    /// Folds `bytes` into the running sum as successive big-endian 16-bit
+    /// words. If a previous call left a trailing odd byte, it is paired with
+    /// the first byte of `bytes` before continuing. If `bytes` itself ends on
+    /// an odd byte, that byte is stashed as the new trailing byte rather than
+    /// zero-padded immediately, so it can still be paired with a future call.
+    pub fn add_bytes(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes;
+
+        if let Some(high) = self.trailing_byte.take() {
+            if let Some((&low, rest)) = chunks.split_first() {
+                self.sum += u16::from_be_bytes([high, low]) as u32;
+                chunks = rest;
+            } else {
+                // No new bytes to pair with; keep waiting for one.
+                self.trailing_byte = Some(high);
+                return;
+            }
+        }
+
+        let mut pairs = chunks.chunks_exact(2);
+        for pair in &mut pairs {
+            self.sum += u16::from_be_bytes([pair[0], pair[1]]) as u32;
+        }
+
+        if let [last] = pairs.remainder() {
+            self.trailing_byte = Some(*last);
+        }
+    }
+
+    /// Folds the accumulated carries and returns the one's-complement
+    /// checksum, treating any pending trailing byte as the high byte of a
+    /// final zero-padded word.
+    pub fn checksum(&self) -> u16 {
+        let mut sum = self.sum;
+        if let Some(high) = self.trailing_byte {
+            sum += (high as u32) << 8;
+        }
+        while sum >> 16 != 0 {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        !(sum as u16)
+    }
+}
+
+/// Computes the TCP/UDP pseudo-header contribution to a transport checksum,
+/// as defined in RFC 793/RFC 768: the source and destination addresses, the
+/// protocol number (zero-extended to 16 bits), and the transport segment
+/// length, all summed as big-endian 16-bit words.
+///
+/// Callers seed a `Checksum` with the result before adding the transport
+/// header and payload bytes.
+pub fn pseudo_header_checksum(src_addr: &[u8], dst_addr: &[u8], protocol: u8, length: u16) -> Checksum {
+    let mut checksum = Checksum::new();
+    checksum.add_bytes(src_addr);
+    checksum.add_bytes(dst_addr);
+    checksum.add_bytes(&[0, protocol]);
+    checksum.add_bytes(&length.to_be_bytes());
+    checksum
+}
+
+/// Incrementally recomputes a stored Internet checksum after a single
+/// 16-bit field changes, as defined in RFC 1624 ("Computation of the
+/// Internet Checksum via Incremental Update"): `HC' = ~(~HC + ~m + m')`.
+///
+/// `old_checksum` is the checksum currently stored in the header (already
+/// one's-complemented), `old_word` is the big-endian 16-bit value the field
+/// held before the edit, and `new_word` is its replacement. This lets a
+/// single-field rewrite (a NAT'd address, a decremented TTL) patch the
+/// checksum in O(1) instead of rescanning the whole header/payload with
+/// [`compute_checksum`]/[`Checksum`].
+///
+/// All intermediate additions are carried out in 32 bits, with end-around
+/// carry folded back in until the result fits in 16 bits, before the final
+/// bitwise NOT — the same folding [`fold_checksum`] does. The RFC1071
+/// one's-complement sum naturally lands on `0x0000` for some inputs; unlike
+/// the caller-facing UDP convention of substituting `0xffff` for a computed
+/// zero checksum, this function returns that raw folded value unmodified —
+/// callers that need the substitution apply it themselves.
+pub fn update_checksum(old_checksum: u16, old_word: u16, new_word: u16) -> u16 {
+    let mut sum = (!old_checksum as u32) + (!old_word as u32) + (new_word as u32);
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Applies a batch of [`update_checksum`] edits to `checksum` in sequence.
+///
+/// Each `(offset, old_word, new_word)` triple names the byte offset of the
+/// 16-bit field that changed, for the caller's own bookkeeping — the
+/// incremental formula itself only depends on a field's old and new values,
+/// not its position.
+pub fn update_checksum_batch(checksum: u16, edits: &[(usize, u16, u16)]) -> u16 {
+    edits
+        .iter()
+        .fold(checksum, |checksum, &(_offset, old_word, new_word)| {
+            update_checksum(checksum, old_word, new_word)
+        })
+}
+
+/// Computes the checksum a `SerializableLayer::serialize_to` implementation
+/// should patch into its checksum field when
+/// [`SerializeOptions::compute_checksums`](crate::rtpacket::writer::SerializeOptions::compute_checksums)
+/// is set.
+///
+/// `header` is the layer's just-written header bytes with the checksum field
+/// zeroed, and `payload` is the remaining `buffer.bytes()` it wraps. Layers
+/// whose checksum covers a pseudo-header (TCP, UDP) seed `pseudo_header` with
+/// [`pseudo_header_checksum`]; layers without one (IPv4) pass
+/// `Checksum::new()`.
+///
+/// Callers are expected to write the returned value back into the header's
+/// checksum field in big-endian byte order.
+pub fn compute_layer_checksum(mut pseudo_header: Checksum, header: &[u8], payload: &[u8]) -> u16 {
+    pseudo_header.add_bytes(header);
+    pseudo_header.add_bytes(payload);
+    pseudo_header.checksum()
+}
+
 #[cfg(test)]
 mod tests {
     use std::convert::TryInto;
@@ -98,5 +242,116 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_incremental_checksum_matches_whole_buffer() {
+        let header = "4540005800000000ff11ffff0aeb1d070aed8877";
+        let mut bytes = hex::decode(header).expect("Failed to decode header");
+        bytes[10] = 0;
+        bytes[11] = 0;
+
+        let mut whole = Checksum::new();
+        whole.add_bytes(&bytes);
+
+        for split in 0..bytes.len() {
+            let (a, b) = bytes.split_at(split);
+            let mut split_checksum = Checksum::new();
+            split_checksum.add_bytes(a);
+            split_checksum.add_bytes(b);
+
+            assert_eq!(
+                split_checksum.checksum(),
+                whole.checksum(),
+                "splitting add_bytes at {} should not change the result",
+                split
+            );
+        }
+    }
+
+    #[test]
+    fn test_incremental_checksum_rfc_vector() {
+        let header = "45000073000040004011b861c0a80001c0a800c7";
+        let mut bytes = hex::decode(header).expect("Failed to decode header");
+        bytes[10] = 0;
+        bytes[11] = 0;
+
+        let mut checksum = Checksum::new();
+        checksum.add_bytes(&bytes[..7]);
+        checksum.add_bytes(&bytes[7..]);
+
+        assert_eq!(checksum.checksum(), 0xb861);
+    }
+
+    #[test]
+    fn test_compute_layer_checksum_no_pseudo_header() {
+        let header = "45000073000040004011b861c0a80001c0a800c7";
+        let mut bytes = hex::decode(header).expect("Failed to decode header");
+        bytes[10] = 0;
+        bytes[11] = 0;
+
+        let checksum = compute_layer_checksum(Checksum::new(), &bytes, &[]);
+
+        assert_eq!(checksum, 0xb861);
+    }
+
+    #[test]
+    fn test_compute_layer_checksum_with_pseudo_header_matches_manual_fold() {
+        let pseudo = pseudo_header_checksum(&[10, 0, 0, 1], &[10, 0, 0, 2], 6, 12);
+        let header = [0u8, 80, 0, 443, 0, 0, 0, 0];
+        let payload = b"hi";
+
+        let via_helper = compute_layer_checksum(pseudo, &header, payload);
+
+        let mut manual = pseudo;
+        manual.add_bytes(&header);
+        manual.add_bytes(payload);
+
+        assert_eq!(via_helper, manual.checksum());
+    }
+
+    #[test]
+    fn update_checksum_matches_a_full_recompute_after_editing_one_word() {
+        let mut bytes = hex::decode("45000073000040004011b861c0a80001c0a800c7").unwrap();
+        bytes[10] = 0;
+        bytes[11] = 0;
+        let original = fold_checksum(compute_checksum(&bytes, 0));
+
+        // Decrement the TTL (bytes[8]) by one, leaving the protocol byte
+        // (bytes[9]) untouched, e.g. as a router would on forwarding.
+        let old_word = u16::from_be_bytes([bytes[8], bytes[9]]);
+        let new_word = old_word - 0x0100;
+        bytes[8..10].copy_from_slice(&new_word.to_be_bytes());
+
+        let recomputed = fold_checksum(compute_checksum(&bytes, 0));
+        let incremental = update_checksum(original, old_word, new_word);
+
+        assert_eq!(incremental, recomputed);
+    }
+
+    #[test]
+    fn update_checksum_preserves_a_computed_zero_result() {
+        assert_eq!(update_checksum(0, 0, 0), 0x0000);
+    }
+
+    #[test]
+    fn update_checksum_batch_matches_a_full_recompute_after_sequential_edits() {
+        let mut bytes = hex::decode("45000073000040004011b861c0a80001c0a800c7").unwrap();
+        bytes[10] = 0;
+        bytes[11] = 0;
+        let original = fold_checksum(compute_checksum(&bytes, 0));
+
+        let edits = [
+            (8usize, u16::from_be_bytes([bytes[8], bytes[9]]), 0x3f11u16),
+            (12usize, u16::from_be_bytes([bytes[12], bytes[13]]), 0xc0a9u16),
+        ];
+        for &(offset, _old, new) in &edits {
+            bytes[offset..offset + 2].copy_from_slice(&new.to_be_bytes());
+        }
+
+        let recomputed = fold_checksum(compute_checksum(&bytes, 0));
+        let incremental = update_checksum_batch(original, &edits);
+
+        assert_eq!(incremental, recomputed);
+    }
 }
 
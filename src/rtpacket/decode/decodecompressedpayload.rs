@@ -0,0 +1,200 @@
+use std::cell::RefCell;
+use std::io::{self, Read};
+use std::rc::Rc;
+
+use flate2::read::{DeflateDecoder, GzDecoder};
+
+use crate::rtpacket::base::decompressedlayer::{CompressionEncoding, DecompressedLayer};
+use crate::rtpacket::base::Layer;
+use crate::rtpacket::decode::decodepayload::create_decode_payload;
+use crate::rtpacket::decode::PacketBuilder;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
+use crate::rtpacket::error::ErrorDecodeable;
+
+/// Gzip's two-byte magic number (RFC 1952 section 2.3.1).
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Sniffs `data`'s leading bytes for a recognized compression format, only
+/// reporting one that `decode_compressed_payload` actually knows how to
+/// inflate.
+///
+/// Gzip is detected by its magic number, and deflate by the zlib header
+/// heuristic (the `CMF` byte's low nibble is 8, i.e. the "deflate"
+/// compression method, and the 16-bit big-endian header is a multiple of
+/// 31, the check value zlib headers are required to satisfy). Brotli has
+/// no magic bytes of its own, so it's only reported when `assume_brotli`
+/// says the caller already knows the payload is brotli encoded (e.g. from
+/// an HTTP `Content-Encoding: br` header) — there's nothing in the bytes
+/// themselves to sniff it from.
+fn sniff_encoding(data: &[u8], assume_brotli: bool) -> Option<CompressionEncoding> {
+    if data.starts_with(&GZIP_MAGIC) {
+        return Some(CompressionEncoding::Gzip);
+    }
+    if data.len() >= 2 && data[0] & 0x0f == 8 && u16::from_be_bytes([data[0], data[1]]) % 31 == 0 {
+        return Some(CompressionEncoding::Deflate);
+    }
+    if assume_brotli {
+        return Some(CompressionEncoding::Brotli);
+    }
+    None
+}
+
+/// Inflates `data`, which is assumed to already be confirmed as `encoding`.
+///
+/// Returns the raw `io::Error` (rather than a `DecodeError`) so the caller
+/// can tell a stream that ended mid-frame (`io::ErrorKind::UnexpectedEof`)
+/// apart from any other inflation failure before wrapping it.
+fn inflate(data: &[u8], encoding: CompressionEncoding) -> io::Result<Vec<u8>> {
+    let mut inflated = Vec::new();
+    let result = match encoding {
+        CompressionEncoding::Gzip => GzDecoder::new(data).read_to_end(&mut inflated),
+        CompressionEncoding::Deflate => DeflateDecoder::new(data).read_to_end(&mut inflated),
+        CompressionEncoding::Brotli => brotli::Decompressor::new(data, 4096).read_to_end(&mut inflated),
+    };
+
+    result.map(|_| inflated)
+}
+
+/// Decodes a payload that may be gzip/deflate/brotli compressed, as HTTP
+/// and other application-layer protocols frequently send.
+///
+/// If `builder`'s `DecodeOptions::decompress_payloads` is unset, or no
+/// supported compression format is detected in `data` (see
+/// [`sniff_encoding`]), this falls through to `create_decode_payload`'s
+/// plain pass-through behavior, so existing callers keep seeing the raw,
+/// still-encoded bytes exactly as before.
+///
+/// Otherwise, it inflates `data` and records both the still-encoded bytes
+/// (via `DecompressedLayer::original`) and the inflated bytes (via
+/// `DecompressedLayer::layer_payload`) on a single new layer, then
+/// re-enters the decode loop via `next_decoder` on the decompressed bytes —
+/// so both the original compressed payload and the decoded payload end up
+/// recorded on the packet, without adding a second, indistinguishable
+/// `Payload` layer that would shadow the real one in a `layer_type`-keyed
+/// lookup.
+///
+/// A decompression failure is always surfaced as a `DecodeError`; if the
+/// underlying stream ended mid-frame (as opposed to, say, a corrupt header),
+/// the packet is additionally marked via `DecodeFeedback::set_truncated`.
+/// This is detected via `io::ErrorKind::UnexpectedEof`, which `flate2`
+/// reports for a truncated gzip/deflate stream; whether the `brotli` crate
+/// does the same for a truncated brotli stream is unverified.
+pub fn decode_compressed_payload(
+    data: Rc<[u8]>,
+    builder: Rc<RefCell<dyn PacketBuilder>>,
+) -> Result<(), DecodeError> {
+    let options = builder.borrow().decode_options();
+
+    let encoding = if options.decompress_payloads {
+        sniff_encoding(&data, options.assume_brotli)
+    } else {
+        None
+    };
+
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None => return create_decode_payload(data, builder),
+    };
+
+    let inflated = inflate(&data, encoding).map_err(|err| {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            builder.borrow_mut().set_truncated();
+        }
+        DecodeError::new(
+            &format!("failed to inflate {} payload", encoding.label()),
+            Some(Box::new(err)),
+        )
+    })?;
+
+    let layer: Rc<dyn Layer> = Rc::new(DecompressedLayer::new(encoding, data.clone(), Rc::from(inflated)));
+    builder.borrow_mut().add_layer(layer);
+
+    builder
+        .borrow_mut()
+        .next_decoder(Rc::new(create_decode_payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use crate::rtpacket::decode::decodecompressedpayload::decode_compressed_payload;
+    use crate::rtpacket::decode::PacketBuilder;
+    use crate::rtpacket::packet::decodeoptions::DecodeOptions;
+    use crate::rtpacket::packet::eagerpacket::EagerPacket;
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn passes_through_raw_bytes_when_decompress_payloads_is_unset() {
+        let data: Rc<[u8]> = gzip(b"hello").into();
+        let packet = EagerPacket::new(data.clone(), DecodeOptions::default());
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+
+        decode_compressed_payload(data, builder).unwrap();
+
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        assert_eq!(
+            packet.layers.len(),
+            1,
+            "decompress_payloads is unset, so the gzip bytes should be left alone as a plain Payload"
+        );
+    }
+
+    #[test]
+    fn inflates_a_gzip_payload_and_resumes_decoding() {
+        let plaintext = b"hello, decompressed world";
+        let data: Rc<[u8]> = gzip(plaintext).into();
+
+        let mut options = DecodeOptions::default();
+        options.decompress_payloads = true;
+        let packet = EagerPacket::new(data.clone(), options);
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+
+        decode_compressed_payload(data, builder).unwrap();
+
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        assert_eq!(
+            packet.layers.len(),
+            2,
+            "the DecompressedLayer and the resumed Payload layer should both be present"
+        );
+        assert_eq!(
+            packet.application.as_ref().and_then(|l| l.payload()).as_deref(),
+            Some(plaintext.as_slice()),
+            "the final application layer should carry the inflated bytes"
+        );
+    }
+
+    #[test]
+    fn marks_the_packet_truncated_when_the_gzip_stream_ends_mid_frame() {
+        let full = gzip(b"hello, decompressed world");
+        let truncated = &full[..full.len() - 4];
+        let data: Rc<[u8]> = truncated.into();
+
+        let mut options = DecodeOptions::default();
+        options.decompress_payloads = true;
+        let packet = EagerPacket::new(data.clone(), options);
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+
+        decode_compressed_payload(data, builder).expect_err("a truncated gzip stream should fail to inflate");
+
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        assert!(
+            packet.metadata.truncated,
+            "a stream ending mid-frame should mark the packet truncated"
+        );
+    }
+}
@@ -1,12 +1,11 @@
 use std::rc::Rc;
 
+use erased_serde::Serialize as _;
+
 use crate::rtpacket::base::{ErrorLayer, Layer};
-use crate::rtpacket::checksum::ChecksumVerificationResult;
 use crate::rtpacket::decode::decoder_builder;
-use crate::rtpacket::error::decodeerror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
 use crate::rtpacket::error::ErrorDecodeable;
-use crate::rtpacket::error::nomethoderror::MethodNotImplementedError;
-use crate::rtpacket::error::PacketError;
 use crate::rtpacket::layertype::{LayerType, LayerTypeID};
 use crate::rtpacket::layertype::LayerTypes::LayerTypeDecodeFailure;
 
@@ -48,21 +47,22 @@ impl DecodeFailure {
         }
     }
 
-    /// Converts the stack bytes to a UTF-8 string for debugging purposes.
-    ///
-    /// This method attempts to interpret the `stack` field's bytes as a UTF-8 encoded string and returns it.
-    /// If the `stack` contains invalid UTF-8 sequences, they are replaced with the Unicode replacement character (�).
+    /// Renders a human-readable report of this decode failure.
     ///
-    /// # Returns
+    /// If `self.err` carries a non-empty `DecodeTrail` (i.e. the error
+    /// unwound through one or more layers via `attach_context`), this
+    /// renders the trail top-to-bottom instead, e.g. "failed at offset 54
+    /// while in TCP after Ethernet/IPv4" rather than an opaque message.
     ///
-    /// A `String` containing the UTF-8 decoded bytes from the `stack`. If the `stack` contains invalid UTF-8,
-    /// non-UTF-8 bytes are replaced with �.
+    /// Otherwise, falls back to interpreting the `stack` field's bytes as a
+    /// UTF-8 encoded string. If `stack` contains invalid UTF-8 sequences,
+    /// they are replaced with the Unicode replacement character (�).
     pub fn dump(&self) -> String {
-        // Directly convert the bytes in `stack` to a String, assuming UTF-8 encoding.
-        // This can fail if `stack` contains invalid UTF-8.
-        // If handling non-UTF-8 or potentially invalid data, consider using lossy conversion
-        // or handling the error more explicitly.
-        String::from_utf8_lossy(&self.stack).into_owned()
+        if self.err.trail().is_empty() {
+            String::from_utf8_lossy(&self.stack).into_owned()
+        } else {
+            self.err.trail().render()
+        }
     }
 }
 
@@ -111,13 +111,6 @@ impl Layer for DecodeFailure {
         None
     }
 
-    fn verify_checksum(&self) -> Result<ChecksumVerificationResult, PacketError> {
-        Err(PacketError::from(MethodNotImplementedError::new(
-            "layer does not verify checksum",
-            None,
-        )))
-    }
-
     /// Provides a descriptive string for the layer.
     ///
     /// This method returns a string that includes the type of the layer and
@@ -132,6 +125,29 @@ impl Layer for DecodeFailure {
         let error_message = format!("DecodeFailure: {:?}", self.err);
         error_message
     }
+
+    fn serialize_fields(
+        &self,
+        serializer: &mut dyn erased_serde::Serializer,
+    ) -> Result<(), erased_serde::Error> {
+        #[derive(serde::Serialize)]
+        struct Fields<'a> {
+            layer_type: &'a str,
+            error: &'a str,
+            contents_hex: Option<String>,
+        }
+
+        Fields {
+            layer_type: self
+                .layer_type
+                .as_ref()
+                .map(|lt| lt.name.as_str())
+                .unwrap_or("DecodeFailure"),
+            error: self.err.message(),
+            contents_hex: self.in_data.as_deref().map(hex::encode),
+        }
+        .erased_serialize(serializer)
+    }
 }
 
 #[cfg(test)]
@@ -139,7 +155,7 @@ mod tests {
     use std::rc::Rc;
 
     use crate::rtpacket::decode::decodefailure::DecodeFailure;
-    use crate::rtpacket::error::decodeerror::DecodeError;
+    use crate::rtpacket::error::packetdecodeerror::DecodeError;
     use crate::rtpacket::error::ErrorDecodeable;
 
     /// Tests that a `DecodeFailure` instance correctly retains and exposes an error message.
@@ -188,4 +204,30 @@ mod tests {
         let dumped_string = decode_failure.dump();
         assert_eq!(dumped_string, "\u{FFFD}\u{FFFD}\u{FFFD}");
     }
+
+    /// Tests that `DecodeFailure::dump` prefers rendering the error's
+    /// `DecodeTrail` over the raw `stack` bytes once a frame has been
+    /// recorded.
+    #[test]
+    fn test_dump_renders_trail_when_present() {
+        use crate::rtpacket::decode::decoder_builder;
+        use crate::rtpacket::layertype::LayerType;
+        use crate::rtpacket::layertype::LayerTypes::LayerTypePayload;
+
+        let data = Rc::from([1, 2, 3]);
+        let layer_type = LayerType {
+            id: LayerTypePayload as crate::rtpacket::layertype::LayerTypeID,
+            name: "TCP".to_owned(),
+            decoder: decoder_builder(LayerTypePayload),
+        };
+        let err = DecodeError::new("invalid option length", None)
+            .attach_context(layer_type, 34, 26, "invalid option length");
+        let decode_failure = DecodeFailure::new(data, err, vec![4, 5, 6]);
+
+        let dumped_string = decode_failure.dump();
+        assert_eq!(
+            dumped_string,
+            "TCP at offset 34 (26 byte(s) remaining): invalid option length"
+        );
+    }
 }
@@ -4,7 +4,8 @@ use std::rc::Rc;
 use crate::rtpacket::base::{ApplicationLayer, Layer, Payloadable};
 use crate::rtpacket::base::fragment::Fragment;
 use crate::rtpacket::decode::{DecodeFunc, PacketBuilder};
-use crate::rtpacket::error::decodererror::{DecodeError, ErrorDecodeable};
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
+use crate::rtpacket::error::ErrorDecodeable;
 
 pub fn fragment_decoder() -> DecodeFunc {
     decode_fragment
@@ -4,7 +4,7 @@ use std::rc::Rc;
 use crate::rtpacket::base::{ApplicationLayer, Layer, Payloadable};
 use crate::rtpacket::base::payload::Payload;
 use crate::rtpacket::decode::PacketBuilder;
-use crate::rtpacket::error::decodererror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
 
 /// Decodes the payload from the provided data and updates the packet builder with the decoded information.
 ///
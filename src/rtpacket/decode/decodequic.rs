@@ -0,0 +1,334 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::rtpacket::base::payload::Payload;
+use crate::rtpacket::base::quic::{QuicHeader, QuicLayer, QuicPacketType};
+use crate::rtpacket::base::{ApplicationLayer, Layer};
+use crate::rtpacket::decode::PacketBuilder;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
+use crate::rtpacket::error::ErrorDecodeable;
+
+/// QUIC Version 2's packet-type codepoint permutation (RFC 9369 section
+/// 3.2): a Version 2 long header's 2-bit packet-type field holds the
+/// Version 1 codepoint plus one, modulo four.
+const QUIC_VERSION_2: u32 = 0x6b33_43cf;
+
+/// Largest connection ID length a QUIC long header may declare (RFC 9000
+/// section 17.2).
+const MAX_CONNECTION_ID_LEN: usize = 20;
+
+/// Length, in bytes, of a long header's version field.
+const VERSION_LEN: usize = 4;
+
+/// Structurally decodes a QUIC packet header (RFC 9000 section 17), the way
+/// neqo's own header parse does: no connection keys are needed, since
+/// everything read here precedes the packet's protected payload.
+///
+/// `data`'s first byte selects long (`0x80` set) or short header form. A
+/// long header carries its own destination/source connection ID lengths; a
+/// short header's destination connection ID has no length of its own in the
+/// wire format, so this falls back to
+/// `DecodeOptions::quic_short_header_dcid_len`, failing if the caller hasn't
+/// set it. A version of all zero bits is recognized as Version Negotiation
+/// ([`QuicPacketType::VersionNegotiation`]) rather than read through the
+/// usual packet-type bits, which carry no defined meaning on that packet
+/// type.
+///
+/// Everything past the parsed header is the still-protected remainder,
+/// recorded as a plain `Payload` layer (and the packet's application layer)
+/// directly — there's no further structural decoding to defer to without
+/// the connection's keys.
+///
+/// Fewer bytes than a declared length requires (the first byte, the version,
+/// a connection ID length's own byte, or the connection ID/destination
+/// bytes it declares) marks the packet truncated via
+/// `DecodeFeedback::set_truncated` and returns a `DecodeError`.
+pub fn decode_quic(
+    data: Rc<[u8]>,
+    builder: Rc<RefCell<dyn PacketBuilder>>,
+) -> Result<(), DecodeError> {
+    if data.is_empty() {
+        builder.borrow_mut().set_truncated();
+        return Err(DecodeError::new(
+            "QUIC packet is empty, missing the header's first byte",
+            None,
+        ));
+    }
+
+    let first_byte = data[0];
+    let fixed_bit = first_byte & 0x40 != 0;
+
+    let (header, header_len) = if first_byte & 0x80 != 0 {
+        decode_long_header(&data, first_byte, fixed_bit, &builder)?
+    } else {
+        let dcid_len = builder
+            .borrow()
+            .decode_options()
+            .quic_short_header_dcid_len
+            .ok_or_else(|| {
+                DecodeError::new(
+                    "cannot decode a QUIC short header without \
+                     DecodeOptions::quic_short_header_dcid_len set: short headers carry no \
+                     destination connection ID length of their own",
+                    None,
+                )
+            })?;
+        decode_short_header(&data, first_byte, fixed_bit, dcid_len as usize, &builder)?
+    };
+
+    let header_bytes: Rc<[u8]> = Rc::from(&data[..header_len]);
+    let remainder: Rc<[u8]> = Rc::from(&data[header_len..]);
+
+    let quic_layer: Rc<dyn Layer> = Rc::new(QuicLayer::new(header_bytes, header));
+    builder.borrow_mut().add_layer(quic_layer);
+
+    let payload = Payload::new_from(remainder);
+    let payload_as_layer: Rc<dyn Layer> = Rc::new(payload.clone());
+    let payload_as_application_layer: Rc<dyn ApplicationLayer> = Rc::new(payload);
+    builder.borrow_mut().add_layer(payload_as_layer);
+    builder
+        .borrow_mut()
+        .set_application_layer(payload_as_application_layer);
+
+    Ok(())
+}
+
+fn decode_long_header(
+    data: &[u8],
+    first_byte: u8,
+    fixed_bit: bool,
+    builder: &Rc<RefCell<dyn PacketBuilder>>,
+) -> Result<(QuicHeader, usize), DecodeError> {
+    if data.len() < 1 + VERSION_LEN {
+        builder.borrow_mut().set_truncated();
+        return Err(DecodeError::new(
+            "QUIC long header truncated before its 4-byte version field",
+            None,
+        ));
+    }
+    let version = u32::from_be_bytes(data[1..1 + VERSION_LEN].try_into().unwrap());
+
+    let (dest_connection_id, offset) = read_connection_id(data, 1 + VERSION_LEN, builder)?;
+    let (source_connection_id, offset) = read_connection_id(data, offset, builder)?;
+
+    let packet_type = if version == 0 {
+        QuicPacketType::VersionNegotiation
+    } else {
+        packet_type_from_bits((first_byte & 0x30) >> 4, version)
+    };
+
+    Ok((
+        QuicHeader::Long {
+            fixed_bit,
+            packet_type,
+            version,
+            dest_connection_id,
+            source_connection_id,
+        },
+        offset,
+    ))
+}
+
+/// Reads a 1-byte connection ID length followed by that many ID bytes,
+/// starting at `offset`, returning the ID and the offset just past it.
+fn read_connection_id(
+    data: &[u8],
+    offset: usize,
+    builder: &Rc<RefCell<dyn PacketBuilder>>,
+) -> Result<(Rc<[u8]>, usize), DecodeError> {
+    if offset >= data.len() {
+        builder.borrow_mut().set_truncated();
+        return Err(DecodeError::new(
+            "QUIC long header truncated before a connection ID length byte",
+            None,
+        ));
+    }
+    let len = data[offset] as usize;
+    let offset = offset + 1;
+
+    if len > MAX_CONNECTION_ID_LEN {
+        return Err(DecodeError::new(
+            &format!(
+                "QUIC long header declares a {len} byte connection ID, over the \
+                 {MAX_CONNECTION_ID_LEN} byte limit"
+            ),
+            None,
+        ));
+    }
+    if data.len() < offset + len {
+        builder.borrow_mut().set_truncated();
+        return Err(DecodeError::new(
+            &format!("QUIC long header declares a {len} byte connection ID, past the end of the packet"),
+            None,
+        ));
+    }
+
+    Ok((Rc::from(&data[offset..offset + len]), offset + len))
+}
+
+fn decode_short_header(
+    data: &[u8],
+    first_byte: u8,
+    fixed_bit: bool,
+    dcid_len: usize,
+    builder: &Rc<RefCell<dyn PacketBuilder>>,
+) -> Result<(QuicHeader, usize), DecodeError> {
+    let spin_bit = first_byte & 0x20 != 0;
+    let key_phase = first_byte & 0x04 != 0;
+
+    if data.len() < 1 + dcid_len {
+        builder.borrow_mut().set_truncated();
+        return Err(DecodeError::new(
+            "QUIC short header truncated before its destination connection ID",
+            None,
+        ));
+    }
+
+    Ok((
+        QuicHeader::Short {
+            fixed_bit,
+            spin_bit,
+            key_phase,
+            dest_connection_id: Rc::from(&data[1..1 + dcid_len]),
+        },
+        1 + dcid_len,
+    ))
+}
+
+/// Maps a long header's raw 2-bit packet-type field to its semantic
+/// meaning, applying QUIC Version 2's codepoint permutation first if
+/// `version` is Version 2 (see [`QUIC_VERSION_2`]).
+fn packet_type_from_bits(raw: u8, version: u32) -> QuicPacketType {
+    let raw = if version == QUIC_VERSION_2 { (raw + 1) % 4 } else { raw };
+    match raw {
+        0 => QuicPacketType::Initial,
+        1 => QuicPacketType::ZeroRtt,
+        2 => QuicPacketType::Handshake,
+        3 => QuicPacketType::Retry,
+        _ => unreachable!("a 2-bit field is always in 0..=3"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtpacket::packet::decodeoptions::DecodeOptions;
+    use crate::rtpacket::packet::eagerpacket::EagerPacket;
+
+    fn decode(data: &[u8], options: DecodeOptions) -> Result<Rc<RefCell<EagerPacket>>, DecodeError> {
+        let data: Rc<[u8]> = Rc::from(data);
+        let packet = EagerPacket::new(data.clone(), options);
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+        decode_quic(data, builder)?;
+        Ok(packet_handle)
+    }
+
+    #[test]
+    fn decodes_a_long_header_initial_packet() {
+        let mut data = vec![0x80 | 0x40 | 0x00]; // long, fixed bit set, Initial (00)
+        data.extend_from_slice(&1u32.to_be_bytes()); // version 1
+        data.push(4); // dcid len
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        data.push(2); // scid len
+        data.extend_from_slice(&[5, 6]);
+        data.extend_from_slice(b"protected");
+
+        let packet_handle = decode(&data, DecodeOptions::default()).unwrap();
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        assert_eq!(packet.layers.len(), 2, "the QUIC layer and the protected Payload layer");
+
+        let quic_layer = packet
+            .layers
+            .iter()
+            .find_map(|layer| {
+                let string = layer.string();
+                string.starts_with("QUIC long header").then_some(string)
+            })
+            .expect("a QUIC long header layer should be present");
+        assert!(quic_layer.contains("Initial"));
+
+        assert_eq!(
+            packet.application.as_ref().and_then(|l| l.payload()).as_deref(),
+            Some(b"protected".as_slice())
+        );
+    }
+
+    #[test]
+    fn version_2_permutes_the_packet_type_codepoint() {
+        // Version 2's "add one modulo four" convention maps raw codepoint 3
+        // (bits 0x30) to (3 + 1) % 4 == 0, i.e. Initial.
+        let mut data = vec![0x80 | 0x40 | 0x30];
+        data.extend_from_slice(&QUIC_VERSION_2.to_be_bytes());
+        data.push(0);
+        data.push(0);
+
+        let packet_handle = decode(&data, DecodeOptions::default()).unwrap();
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        let quic_layer = packet.layers[0].string();
+        assert!(quic_layer.contains("Initial"), "got: {quic_layer}");
+    }
+
+    #[test]
+    fn zero_version_is_recognized_as_version_negotiation() {
+        let mut data = vec![0x80 | 0x40];
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.push(0);
+        data.push(0);
+
+        let packet_handle = decode(&data, DecodeOptions::default()).unwrap();
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        assert!(packet.layers[0].string().contains("VersionNegotiation"));
+    }
+
+    #[test]
+    fn decodes_a_short_header_using_the_configured_dcid_length() {
+        let mut data = vec![0x00 | 0x40 | 0x20]; // short, fixed bit, spin bit
+        data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]); // 8 byte dcid
+        data.extend_from_slice(b"protected");
+
+        let mut options = DecodeOptions::default();
+        options.quic_short_header_dcid_len = Some(8);
+
+        let packet_handle = decode(&data, options).unwrap();
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        assert_eq!(packet.layers.len(), 2);
+        assert!(packet.layers[0].string().contains("short header"));
+        assert_eq!(
+            packet.application.as_ref().and_then(|l| l.payload()).as_deref(),
+            Some(b"protected".as_slice())
+        );
+    }
+
+    #[test]
+    fn short_header_without_a_configured_dcid_length_fails() {
+        let data = vec![0x00];
+        decode(&data, DecodeOptions::default())
+            .expect_err("a short header can't be decoded without quic_short_header_dcid_len set");
+    }
+
+    #[test]
+    fn truncated_long_header_marks_the_packet_truncated() {
+        let data = vec![0x80, 0x00, 0x00]; // long header, version field cut short
+
+        let data: Rc<[u8]> = Rc::from(data);
+        let packet = EagerPacket::new(data.clone(), DecodeOptions::default());
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+
+        decode_quic(data, builder).expect_err("a truncated version field should fail to decode");
+
+        let packet = Rc::try_unwrap(packet_handle).ok().unwrap().into_inner();
+        assert!(packet.metadata.truncated);
+    }
+
+    #[test]
+    fn an_oversized_connection_id_length_is_rejected() {
+        let mut data = vec![0x80 | 0x40];
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.push(21); // over the 20 byte limit
+
+        decode(&data, DecodeOptions::default())
+            .expect_err("a connection ID length over 20 bytes should be rejected");
+    }
+}
@@ -0,0 +1,142 @@
+use crate::rtpacket::layertype::LayerType;
+
+/// A single frame in a [`DecodeTrail`], recording that a layer was entered at
+/// a given position in the original bytes when a decode error unwound
+/// through it.
+#[derive(Debug, Clone)]
+pub struct TrailFrame {
+    pub layer: LayerType,
+    pub byte_offset: usize,
+    pub remaining: usize,
+    pub note: String,
+}
+
+/// Accumulates a [`TrailFrame`] per layer as a decode error unwinds, borrowing
+/// winnow's notion of an error that builds up context frames on its way out
+/// rather than capturing everything at the root. [`DecodeFailure::dump`]
+/// (crate::rtpacket::decode::decodefailure::DecodeFailure) renders this
+/// top-to-bottom so a multi-layer decode failure reads as e.g. "failed at
+/// offset 54 while in TCP after Ethernet/IPv4" instead of an opaque message.
+#[derive(Debug, Clone, Default)]
+pub struct DecodeTrail {
+    frames: Vec<TrailFrame>,
+}
+
+impl DecodeTrail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `layer` was entered `byte_offset` bytes into the original
+    /// data, with `remaining` bytes left for it (and any further layers) to
+    /// decode, annotated with `note` (typically the error that caused this
+    /// frame to be recorded).
+    ///
+    /// As an error unwinds through nested layer decodes, the innermost layer
+    /// (closest to the actual failure) calls this first and the outermost
+    /// layer calls it last, so the frame is inserted at the front: `frames()`
+    /// and `render()` always read outermost-first, regardless of push order.
+    pub fn push(&mut self, layer: LayerType, byte_offset: usize, remaining: usize, note: impl Into<String>) {
+        self.frames.insert(
+            0,
+            TrailFrame {
+                layer,
+                byte_offset,
+                remaining,
+                note: note.into(),
+            },
+        );
+    }
+
+    pub fn frames(&self) -> &[TrailFrame] {
+        &self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Renders the trail top-to-bottom, one line per frame, outermost layer
+    /// first.
+    pub fn render(&self) -> String {
+        self.frames
+            .iter()
+            .map(|frame| {
+                format!(
+                    "{} at offset {} ({} byte(s) remaining): {}",
+                    frame.layer.name, frame.byte_offset, frame.remaining, frame.note
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders the trail as a single-line, innermost-first chain, e.g.
+    /// "while decoding TCP -> while decoding IPv4 -> while decoding Ethernet",
+    /// for [`PacketDecodeError`](crate::rtpacket::error::packetdecodeerror::PacketDecodeError)'s
+    /// `Display` impl to print above the backtrace. Each frame's layer name
+    /// doubles as its decode step's name, since `DecodeFunc` is a bare `fn`
+    /// pointer with no reflectable name of its own at runtime.
+    pub fn render_chain(&self) -> String {
+        self.frames
+            .iter()
+            .rev()
+            .map(|frame| format!("while decoding {}", frame.layer.name))
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtpacket::decode::decoder_builder;
+    use crate::rtpacket::layertype::LayerTypes::LayerTypePayload;
+
+    fn layer(name: &str) -> LayerType {
+        LayerType {
+            id: LayerTypePayload as crate::rtpacket::layertype::LayerTypeID,
+            name: name.to_owned(),
+            decoder: decoder_builder(LayerTypePayload),
+        }
+    }
+
+    #[test]
+    fn new_trail_is_empty() {
+        let trail = DecodeTrail::new();
+        assert!(trail.is_empty());
+        assert_eq!(trail.render(), "");
+    }
+
+    #[test]
+    fn push_prepends_so_render_reads_outermost_first() {
+        // Frames are recorded in the order an error actually unwinds:
+        // innermost layer (TCP) first, outermost (Ethernet) last.
+        let mut trail = DecodeTrail::new();
+        trail.push(layer("TCP"), 34, 26, "invalid option length");
+        trail.push(layer("IPv4"), 14, 46, "entered IPv4");
+        trail.push(layer("Ethernet"), 0, 60, "entered Ethernet");
+
+        assert!(!trail.is_empty());
+        assert_eq!(trail.frames().len(), 3);
+        assert_eq!(
+            trail.render(),
+            "Ethernet at offset 0 (60 byte(s) remaining): entered Ethernet\n\
+             IPv4 at offset 14 (46 byte(s) remaining): entered IPv4\n\
+             TCP at offset 34 (26 byte(s) remaining): invalid option length"
+        );
+    }
+
+    #[test]
+    fn render_chain_reads_innermost_first() {
+        let mut trail = DecodeTrail::new();
+        trail.push(layer("TCP"), 34, 26, "invalid option length");
+        trail.push(layer("IPv4"), 14, 46, "entered IPv4");
+        trail.push(layer("Ethernet"), 0, 60, "entered Ethernet");
+
+        assert_eq!(
+            trail.render_chain(),
+            "while decoding TCP -> while decoding IPv4 -> while decoding Ethernet"
+        );
+    }
+}
@@ -2,53 +2,47 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 pub use crate::rtpacket::decode::decodefeedback::DecodeFeedback;
-use crate::rtpacket::decode::decodefragment::decode_fragment;
-use crate::rtpacket::decode::decodepayload::create_decode_payload;
 use crate::rtpacket::decode::decodeunknown::create_decode_unknown;
 pub use crate::rtpacket::decode::packetbuilder::PacketBuilder;
-use crate::rtpacket::error::decodeerror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
 pub use crate::rtpacket::layertype::LayerType;
-use crate::rtpacket::layertype::LayerTypes;
+use crate::rtpacket::layertype::{LayerTypeID, LayerTypes};
 
+pub mod decodecompressedpayload;
 pub mod decodefailure;
 pub mod decodefeedback;
 pub mod decodefragment;
 pub mod decodepayload;
+pub mod decodequic;
+pub mod decodetrail;
 
 pub mod decodeunknown;
 pub mod nildecodefeedback;
 pub mod packetbuilder;
-/// Constructs a decoding function based on the specified layer type.
-///
-/// This function serves as a factory or builder that, given a layer type, returns the
-/// appropriate decoding function for that type. It maps different layer types to their
-/// corresponding decoding functions, facilitating dynamic decoding strategy selection
-/// based on the layer type being processed.
-///
-/// # Arguments
-/// * `layer_type`: The type of layer for which a decoding function is required. The `layer_type`
-///   parameter is used to determine which specific decoding function should be returned.
-///
-/// # Returns
-/// * `DecodeFunc`: A function pointer to the decoding function appropriate for the given
-///   layer type. This function can then be called to perform decoding operations on packet data.
+
+/// Looks up the decoding function registered for `layer_type`, falling back
+/// to [`create_decode_unknown`] if nothing is registered.
 ///
-/// # Supported Layer Types
-/// - `LayerTypeZero`: Returns a function for handling unknown or unimplemented layer types.
-/// - `LayerTypeDecodeFailure`: Returns a function for handling decoding failures, typically
-///   used as a fallback or error handling strategy.
-/// - `LayerTypePayload`: Returns a function specifically designed for decoding payload data.
-/// - `LayerTypeFragment`: Returns a function for decoding fragmented data, useful for processing
-///   packets that are part of a larger set or stream of data fragments.
+/// This used to be a fixed `match` over the four built-in layer types, so
+/// adding protocol support meant editing this function directly. It's now a
+/// thin wrapper over `crate::rtpacket::layertype`'s global decoder registry
+/// (the same one [`crate::rtpacket::layertype::lookup_decoder`] backs),
+/// which lets downstream crates register their own decoders — for Ethernet,
+/// IPv4, TCP, or anything else — without patching this module, and lets
+/// tests swap a layer's decoder out via `register_decoder`.
 ///
-/// ```
+/// Every `LayerType` built by this crate bakes this function's return value
+/// into its `decoder` field at construction time, so the four built-ins are
+/// lazily registered here on first use (via [`crate::rtpacket::layertype::register_defaults`])
+/// rather than requiring every caller to remember to register them first. A
+/// `register_decoder` override installed afterwards, whether for a built-in
+/// id or a new one, is unaffected — this only ever runs `register_defaults`
+/// once per process.
 pub fn decoder_builder(layer_type: LayerTypes) -> DecodeFunc {
-    match layer_type {
-        LayerTypes::LayerTypeZero => create_decode_unknown,
-        LayerTypes::LayerTypeDecodeFailure => create_decode_unknown,
-        LayerTypes::LayerTypePayload => create_decode_payload,
-        LayerTypes::LayerTypeFragment => decode_fragment,
-    }
+    static DEFAULTS_REGISTERED: std::sync::Once = std::sync::Once::new();
+    DEFAULTS_REGISTERED.call_once(crate::rtpacket::layertype::register_defaults);
+
+    crate::rtpacket::layertype::lookup_decoder(layer_type as LayerTypeID).unwrap_or(create_decode_unknown)
 }
 
 /// Type alias for a decoding function.
@@ -70,3 +64,17 @@ pub fn decoder_builder(layer_type: LayerTypes) -> DecodeFunc {
 ///   `Ok(())`. On failure, it returns an error boxed as a dynamic `Error` trait object, allowing
 ///   for various types of errors to be returned.
 pub type DecodeFunc = fn(Rc<[u8]>, Rc<RefCell<dyn PacketBuilder>>) -> Result<(), DecodeError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtpacket::decode::decodepayload::create_decode_payload;
+
+    #[test]
+    fn decoder_builder_returns_the_registered_built_in_decoder() {
+        assert_eq!(
+            decoder_builder(LayerTypes::LayerTypePayload) as usize,
+            create_decode_payload as usize
+        );
+    }
+}
@@ -4,7 +4,7 @@ use crate::rtpacket::base::{
     ApplicationLayer, ErrorLayer, Layer, LinkLayer, NetworkLayer, TransportLayer,
 };
 use crate::rtpacket::decode::{DecodeFeedback, DecodeFunc};
-use crate::rtpacket::error::decodeerror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
 use crate::rtpacket::packet::decodeoptions::DecodeOptions;
 
 /// Used by layer decoders to store the layers they've decoded,
@@ -30,6 +30,19 @@ pub trait PacketBuilder: DecodeFeedback {
     /// used to decode the last added layer's payload.
     fn next_decoder(&mut self, next: Rc<DecodeFunc>) -> Result<(), DecodeError>;
 
+    /// Like [`next_decoder`](PacketBuilder::next_decoder), but resolves the
+    /// decoder itself instead of requiring the caller to supply one: it reads
+    /// the `LayerTypeID` the last added layer's
+    /// [`Layer::next_layer_type_id`](crate::rtpacket::base::Layer::next_layer_type_id)
+    /// advertises and looks it up in the global registry
+    /// (`crate::rtpacket::layertype::lookup_decoder`), falling back to the
+    /// fragment decoder if nothing is registered for it (or the last layer
+    /// doesn't advertise one). This turns layer dispatch into a data-driven
+    /// table: new protocol decoders can be plugged in via
+    /// `crate::rtpacket::layertype::register_decoder` without editing the
+    /// core decode loop.
+    fn next_decoder_auto(&mut self) -> Result<(), DecodeError>;
+
     /// Utility method for debugging. Should dump packet data to stderr or
     /// another diagnostic output. Not intended for use in production decoders.
     fn dump_packet_data(&self);
@@ -87,6 +100,10 @@ impl PacketBuilder for MockPacketBuilder {
         todo!()
     }
 
+    fn next_decoder_auto(&mut self) -> Result<(), DecodeError> {
+        todo!()
+    }
+
     fn dump_packet_data(&self) {
         todo!()
     }
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::rtpacket::decode::{decoder_builder, DecodeFunc};
+use crate::rtpacket::layertype::LayerTypes::LayerTypeZero;
+
+/// Identifies the link-layer / capture encapsulation a raw packet buffer
+/// begins with, mirroring the `DLT_*` constants found in a pcap file's
+/// global header. Used to pick the `DecodeFunc` that should decode a
+/// packet's first layer, so callers reading straight from a capture source
+/// don't need to already know which decoder corresponds to its link type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EncapType {
+    Ethernet,
+    RawIP,
+    LinuxSLL,
+    Ieee80211,
+    Loopback,
+}
+
+fn registry() -> &'static RwLock<HashMap<EncapType, DecodeFunc>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<EncapType, DecodeFunc>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Registers `decoder` as the entry-point `DecodeFunc` for `encap`,
+/// overwriting any decoder already registered for that encapsulation.
+pub fn register_encap(encap: EncapType, decoder: DecodeFunc) {
+    registry().write().unwrap().insert(encap, decoder);
+}
+
+/// Returns the entry-point decoder currently registered for `encap`, if any.
+pub fn decoder_for_encap(encap: EncapType) -> Option<DecodeFunc> {
+    registry().read().unwrap().get(&encap).copied()
+}
+
+/// Registers the entry-point decoder for every encapsulation this crate
+/// recognizes. Safe to call more than once; later calls simply re-register
+/// the same defaults, so any previous `register_encap` override is lost.
+///
+/// Until concrete Ethernet/raw-IP/SLL/802.11/loopback dissectors exist, every
+/// encapsulation starts decoding at the generic "unknown layer" decoder;
+/// callers can override any of these with `register_encap` once a real
+/// dissector for that encapsulation is added.
+pub fn register_defaults() {
+    let default_decoder = decoder_builder(LayerTypeZero);
+    for encap in [
+        EncapType::Ethernet,
+        EncapType::RawIP,
+        EncapType::LinuxSLL,
+        EncapType::Ieee80211,
+        EncapType::Loopback,
+    ] {
+        register_encap(encap, default_decoder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::rtpacket::decode::PacketBuilder;
+    use crate::rtpacket::error::packetdecodeerror::DecodeError;
+
+    use super::*;
+
+    fn decode_ok(_data: Rc<[u8]>, _builder: Rc<RefCell<dyn PacketBuilder>>) -> Result<(), DecodeError> {
+        Ok(())
+    }
+
+    // `registry()` is a process-wide global, so both checks live in a
+    // single test to avoid racing with other `#[test]` threads over the
+    // same `EncapType` entries.
+    #[test]
+    fn register_defaults_then_override_round_trips() {
+        register_defaults();
+        for encap in [
+            EncapType::Ethernet,
+            EncapType::RawIP,
+            EncapType::LinuxSLL,
+            EncapType::Ieee80211,
+            EncapType::Loopback,
+        ] {
+            assert!(decoder_for_encap(encap).is_some());
+        }
+
+        register_encap(EncapType::Loopback, decode_ok);
+        let decoder = decoder_for_encap(EncapType::Loopback).expect("should be registered");
+
+        let data: Rc<[u8]> = Rc::from(&[][..]);
+        let builder: Rc<RefCell<dyn PacketBuilder>> =
+            Rc::new(RefCell::new(crate::rtpacket::decode::packetbuilder::MockPacketBuilder {
+                layers_added: vec![],
+                link_layer: None,
+                application_layer: None,
+            }));
+        assert!(decoder(data, builder).is_ok());
+    }
+}
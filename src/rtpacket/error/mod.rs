@@ -1,18 +1,87 @@
-use std::backtrace::Backtrace;
+use std::backtrace::{Backtrace, BacktraceStatus};
 use std::error::Error;
 use std::fmt;
 use std::fmt::Display;
+use std::rc::Rc;
 
-use crate::rtpacket::error::decodeerror::DecodeError;
-use crate::rtpacket::error::nomethoderror::MethodNotImplementedError;
-use crate::rtpacket::error::verifychecksumerror::VerifyChecksumError;
+use crate::rtpacket::error::packetdecodeerror::PacketDecodeError;
+use crate::rtpacket::layertype::LayerType;
 
-pub mod decodeerror;
-pub mod nomethoderror;
-pub mod verifychecksumerror;
+pub mod packetdecodeerror;
 
 pub trait Backtraceable {
     fn backtrace(&self) -> &Backtrace;
+
+    /// Cheaply tests whether `backtrace()` actually captured frames, so
+    /// callers can decide whether it's worth reusing without formatting it.
+    fn backtrace_status(&self) -> BacktraceStatus {
+        self.backtrace().status()
+    }
+}
+
+/// Controls whether (and how) a new [`PacketDecodeError`] captures a
+/// backtrace, analogous to the standard library's own (unstable)
+/// `BacktraceStyle`, but scoped to this crate's decode-recovery path rather
+/// than the process-wide `RUST_BACKTRACE` setting.
+///
+/// `DecodeOptions::backtrace_style` lets a caller pick this once for the
+/// whole decode rather than relying on whatever `RUST_BACKTRACE` happens to
+/// be set to in the environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BacktraceStyle {
+    /// Skip `Backtrace::capture()` entirely. The recovered error's
+    /// backtrace is `Backtrace::disabled()`, so callers on a hot path that
+    /// never inspect it don't pay for collecting frames.
+    Off,
+    /// Capture a backtrace the normal way, honoring `RUST_BACKTRACE`/
+    /// `RUST_LIB_BACKTRACE` exactly as any other `Backtrace::capture()`
+    /// call in the process would. This crate's previous, implicit behavior.
+    #[default]
+    Short,
+    /// Force capture via `Backtrace::force_capture()`, even if
+    /// `RUST_BACKTRACE` is unset.
+    Full,
+}
+
+/// Picks the backtrace a new `ErrorDecodeable::new` call should carry:
+/// if `source` is `Some` and downcasts to our own `Backtraceable`
+/// error type with a `Captured` backtrace, that backtrace is moved forward
+/// (leaving a cheap, disabled placeholder behind in `source`) instead of
+/// capturing a new one. Otherwise, a fresh backtrace is captured.
+///
+/// This keeps a single meaningful trace per error tree: the innermost error
+/// is almost always the one capturing the useful frames, so every
+/// abstraction boundary it bubbles through can skip the cost of capturing
+/// its own.
+pub(crate) fn backtrace_for_new(source: &mut Option<Box<dyn Error>>) -> Backtrace {
+    backtrace_for_new_with_style(source, BacktraceStyle::Short)
+}
+
+/// Like [`backtrace_for_new`], but lets the caller pick the capture
+/// `style` instead of always honoring `RUST_BACKTRACE`. A source's
+/// already-captured backtrace is still reused in preference to capturing a
+/// fresh one, regardless of `style` — `Off` only suppresses capturing a
+/// *new* backtrace, it doesn't discard one that already exists.
+pub(crate) fn backtrace_for_new_with_style(source: &mut Option<Box<dyn Error>>, style: BacktraceStyle) -> Backtrace {
+    match style {
+        BacktraceStyle::Off => take_backtrace_from_source(source).unwrap_or_else(Backtrace::disabled),
+        BacktraceStyle::Short => take_backtrace_from_source(source).unwrap_or_else(Backtrace::capture),
+        BacktraceStyle::Full => take_backtrace_from_source(source).unwrap_or_else(Backtrace::force_capture),
+    }
+}
+
+fn take_backtrace_from_source(source: &mut Option<Box<dyn Error>>) -> Option<Backtrace> {
+    let error = source.as_mut()?;
+    let e = error.downcast_mut::<PacketDecodeError>()?;
+    take_if_captured(e.backtrace_mut())
+}
+
+fn take_if_captured(trace: &mut Backtrace) -> Option<Backtrace> {
+    if trace.status() == BacktraceStatus::Captured {
+        Some(std::mem::replace(trace, Backtrace::disabled()))
+    } else {
+        None
+    }
 }
 
 /// `ErrorDecodeable` is a trait extending `std::error::Error` and `std::fmt::Display`
@@ -42,38 +111,57 @@ pub trait ErrorDecodeable: Error + Display {
 
 #[derive(Debug)]
 pub enum PacketError {
-    Decode(DecodeError),
-    MethodNotImplemented(MethodNotImplementedError),
-    VerifyChecksum(VerifyChecksumError),
-}
-impl From<DecodeError> for PacketError {
-    fn from(error: DecodeError) -> Self {
-        PacketError::Decode(error)
-    }
-}
-
-impl From<VerifyChecksumError> for PacketError {
-    fn from(error: VerifyChecksumError) -> Self {
-        PacketError::VerifyChecksum(error)
-    }
+    Decode(PacketDecodeError),
+    /// A layer decoder consumed the bytes it was handed correctly but
+    /// determined the layer extends past them — it needs more input before
+    /// it can finish, rather than having been handed a malformed record.
+    ///
+    /// No `DecodeFunc` in this crate constructs this directly: that
+    /// signature returns a plain [`PacketDecodeError`], and changing it
+    /// would ripple through every registered decoder. It's defined here so a
+    /// decoder written against `PacketError` directly can report this
+    /// distinctly, and because
+    /// [`crate::rtpacket::packet::streamingdecoder::StreamingDecoder`]
+    /// already constructs it internally (via its private `poll` method)
+    /// whenever its buffer doesn't yet hold a full record, then translates
+    /// it back to `Ok(None)` from `push` — "suspend, keep the buffer, resume
+    /// on the next push".
+    NeedMoreData,
 }
 
-impl From<MethodNotImplementedError> for PacketError {
-    fn from(error: MethodNotImplementedError) -> Self {
-        PacketError::MethodNotImplemented(error)
+impl From<PacketDecodeError> for PacketError {
+    fn from(error: PacketDecodeError) -> Self {
+        PacketError::Decode(error)
     }
 }
 
 impl fmt::Display for PacketError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            PacketError::Decode(e) => write!(f, "DecodeError: {}", e),
-            PacketError::MethodNotImplemented(e) => {
-                write!(f, "MethodNotImplementedError: {}", e)
+            PacketError::Decode(e) => write!(f, "{}: {}", e.kind().label(), e),
+            PacketError::NeedMoreData => {
+                write!(f, "more data is needed before decoding can continue")
             }
-            PacketError::VerifyChecksum(e) => write!(f, "VerifyChecksumError: {}", e),
         }
     }
 }
 
 impl Error for PacketError {}
+
+/// One decode error recorded while
+/// [`crate::rtpacket::packet::decodeoptions::DecodeOptions::accumulate_errors`]
+/// was set, instead of aborting the whole decode at the first failure.
+///
+/// `error` is wrapped in an `Rc` rather than stored by value so that
+/// `DecodeErrorRecord`, and in turn
+/// `crate::rtpacket::packet::eagerpacket::EagerPacket`, can stay cheaply
+/// `Clone` — the same reason `DecodeFailure` holds its error as `Rc<DecodeError>`.
+#[derive(Debug, Clone)]
+pub struct DecodeErrorRecord {
+    /// The layer that was being decoded when `error` arose.
+    pub layer_type: LayerType,
+    /// Byte offset into the packet's original data where `layer_type` began.
+    pub byte_offset: usize,
+    /// The error itself.
+    pub error: Rc<PacketError>,
+}
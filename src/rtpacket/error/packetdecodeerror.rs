@@ -0,0 +1,494 @@
+use std::backtrace::Backtrace;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::rtpacket::decode::decodetrail::DecodeTrail;
+use crate::rtpacket::error::{backtrace_for_new, backtrace_for_new_with_style, BacktraceStyle, Backtraceable, ErrorDecodeable};
+use crate::rtpacket::layertype::LayerType;
+
+/// Distinguishes the handful of scenarios `PacketDecodeError` is raised for,
+/// without requiring a separate struct (and a separate copy of `Display`,
+/// `Error`, `Backtraceable` and `ErrorDecodeable`) per scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Decode,
+    VerifyChecksum,
+    MethodNotImplemented,
+    NoLastLayer,
+}
+
+impl ErrorKind {
+    /// The label `PacketError`'s `Display` impl prefixes the message with,
+    /// matching the names the former per-kind structs used to carry.
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorKind::Decode => "DecodeError",
+            ErrorKind::VerifyChecksum => "VerifyChecksumError",
+            ErrorKind::MethodNotImplemented => "MethodNotImplementedError",
+            ErrorKind::NoLastLayer => "NoLastLayerError",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ErrorRepr {
+    kind: ErrorKind,
+    message: String,
+    stack_trace: Backtrace,
+    source: Option<Box<dyn Error>>,
+    trail: DecodeTrail,
+}
+
+/// `PacketDecodeError` is this crate's single error type for decoding,
+/// checksum verification and method-not-implemented failures. It used to be
+/// three structurally identical structs (`DecodeError`, `VerifyChecksumError`,
+/// `MethodNotImplementedError`); they're now a single narrow (one word)
+/// pointer carrying an [`ErrorKind`] discriminant instead, and the old names
+/// live on as type aliases so existing signatures keep compiling unchanged.
+#[derive(Debug)]
+pub struct PacketDecodeError(Box<ErrorRepr>);
+
+impl Display for PacketDecodeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.0.trail.is_empty() {
+            write!(f, "{}\nStack trace:\n{:?}", self.0.message, self.0.stack_trace)
+        } else {
+            write!(
+                f,
+                "{}\n{}\nStack trace:\n{:?}",
+                self.0.message,
+                self.0.trail.render_chain(),
+                self.0.stack_trace
+            )
+        }
+    }
+}
+
+impl Error for PacketDecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source.as_deref()
+    }
+}
+
+impl ErrorDecodeable for PacketDecodeError {
+    /// Constructs a new `PacketDecodeError` of [`ErrorKind::Decode`], reusing
+    /// `source`'s backtrace if it has one already captured rather than
+    /// capturing a fresh one. Use [`PacketDecodeError::with_kind`] to build
+    /// one of the other kinds.
+    fn new(message: &str, source: Option<Box<dyn Error>>) -> Self {
+        PacketDecodeError::with_kind(ErrorKind::Decode, message, source)
+    }
+
+    fn message(&self) -> &str {
+        &self.0.message
+    }
+}
+
+impl Backtraceable for PacketDecodeError {
+    fn backtrace(&self) -> &Backtrace {
+        &self.0.stack_trace
+    }
+}
+
+impl PacketDecodeError {
+    /// Constructs a new `PacketDecodeError` of the given `kind`, reusing
+    /// `source`'s backtrace if it has one already captured rather than
+    /// capturing a fresh one.
+    pub fn with_kind(kind: ErrorKind, message: &str, source: Option<Box<dyn Error>>) -> Self {
+        let mut source = source;
+        let stack_trace = backtrace_for_new(&mut source);
+        PacketDecodeError(Box::new(ErrorRepr {
+            kind,
+            message: message.to_string(),
+            stack_trace,
+            source,
+            trail: DecodeTrail::new(),
+        }))
+    }
+
+    /// Like [`PacketDecodeError::with_kind`], but lets the caller pick the
+    /// backtrace capture `style` instead of always honoring `RUST_BACKTRACE`.
+    ///
+    /// `ErrorDecodeable::new`/`with_kind` aren't given a `style` parameter
+    /// directly: they're called from dozens of decoder call sites
+    /// throughout this crate (see `decodequic.rs`, `decodeunknown.rs`, the
+    /// `define_layer!` macro, ...), none of which have a `DecodeOptions` in
+    /// scope to pick a style from. This constructor exists for the one call
+    /// site that does: `EagerPacket::recover_decode_error`, the panic-recovery
+    /// path, which reads `DecodeOptions::backtrace_style` before building the
+    /// error that becomes its `DecodeFailure` layer.
+    pub fn with_backtrace_style(
+        kind: ErrorKind,
+        message: &str,
+        source: Option<Box<dyn Error>>,
+        style: BacktraceStyle,
+    ) -> Self {
+        let mut source = source;
+        let stack_trace = backtrace_for_new_with_style(&mut source, style);
+        PacketDecodeError(Box::new(ErrorRepr {
+            kind,
+            message: message.to_string(),
+            stack_trace,
+            source,
+            trail: DecodeTrail::new(),
+        }))
+    }
+
+    /// The scenario this error was raised for.
+    pub fn kind(&self) -> ErrorKind {
+        self.0.kind
+    }
+
+    /// Attaches a context frame recording that `layer` was entered
+    /// `byte_offset` bytes into the original data, with `remaining` bytes
+    /// left for decoding, as this error unwinds through it. Callers thread
+    /// this through the decode path (e.g. each `PacketBuilder::next_decoder`
+    /// call wraps the next layer's decode with a frame) so `Display` and
+    /// [`DecodeFailure::dump`] (crate::rtpacket::decode::decodefailure::DecodeFailure)
+    /// can report exactly where in the byte stream decoding broke down.
+    pub fn attach_context(mut self, layer: LayerType, byte_offset: usize, remaining: usize, note: &str) -> Self {
+        self.0.trail.push(layer, byte_offset, remaining, note);
+        self
+    }
+
+    /// The trail of layers this error unwound through, outermost first.
+    /// Empty if nothing has called [`PacketDecodeError::attach_context`].
+    pub fn trail(&self) -> &DecodeTrail {
+        &self.0.trail
+    }
+
+    /// Exposes the backtrace for in-place replacement, so `error::mod`'s
+    /// `backtrace_for_new` can move it out into a new error without capturing
+    /// a fresh one.
+    pub(crate) fn backtrace_mut(&mut self) -> &mut Backtrace {
+        &mut self.0.stack_trace
+    }
+
+    /// Wraps this error as the `source` of a new `PacketDecodeError` of the
+    /// same kind carrying `msg`, so higher-level decoders can attach context
+    /// as the error bubbles up (e.g. `"failed to decode TCP layer"` wrapping
+    /// `"invalid option length"`) without losing the original error.
+    ///
+    /// The new error reuses this error's already-captured backtrace instead
+    /// of capturing a fresh one, since the original decode site is almost
+    /// always more useful than wherever `context` happened to be called.
+    pub fn context(self, msg: &str) -> PacketDecodeError {
+        let mut original = self;
+        let stack_trace = std::mem::replace(&mut original.0.stack_trace, Backtrace::disabled());
+        let kind = original.0.kind;
+        PacketDecodeError(Box::new(ErrorRepr {
+            kind,
+            message: msg.to_string(),
+            stack_trace,
+            source: Some(Box::new(original)),
+            trail: DecodeTrail::new(),
+        }))
+    }
+
+    /// Iterates this error's cause chain, starting with `self` and following
+    /// [`Error::source`] until it returns `None`.
+    pub fn chain(&self) -> Chain<'_> {
+        Chain { next: Some(self) }
+    }
+
+    /// Returns the last error in this error's cause chain, i.e. the
+    /// original, innermost failure.
+    pub fn root_cause(&self) -> &(dyn Error + 'static) {
+        self.chain()
+            .last()
+            .expect("chain always yields at least `self`")
+    }
+}
+
+/// Iterator over an error's cause chain, returned by [`PacketDecodeError::chain`].
+pub struct Chain<'a> {
+    next: Option<&'a (dyn Error + 'static)>,
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = current.source();
+        Some(current)
+    }
+}
+
+/// `Result` extension mirroring [`PacketDecodeError::context`], for attaching
+/// context to a fallible decode step without an intermediate `match`.
+pub trait DecodeContext<T> {
+    /// Attaches `msg` as context if this result is an error. See
+    /// [`PacketDecodeError::context`].
+    fn context(self, msg: &str) -> Result<T, PacketDecodeError>;
+
+    /// Like [`DecodeContext::context`], but only builds the message if this
+    /// result is actually an error.
+    fn with_context<F, S>(self, f: F) -> Result<T, PacketDecodeError>
+    where
+        F: FnOnce() -> S,
+        S: AsRef<str>;
+}
+
+impl<T> DecodeContext<T> for Result<T, PacketDecodeError> {
+    fn context(self, msg: &str) -> Result<T, PacketDecodeError> {
+        self.map_err(|err| err.context(msg))
+    }
+
+    fn with_context<F, S>(self, f: F) -> Result<T, PacketDecodeError>
+    where
+        F: FnOnce() -> S,
+        S: AsRef<str>,
+    {
+        self.map_err(|err| err.context(f().as_ref()))
+    }
+}
+
+/// These type aliases preserve the names the crate's scenarios used to have
+/// their own struct for, now that they're all the same type distinguished by
+/// [`ErrorKind`].
+pub type DecodeError = PacketDecodeError;
+pub type VerifyChecksumError = PacketDecodeError;
+pub type MethodNotImplementedError = PacketDecodeError;
+pub type NoLastLayerError = PacketDecodeError;
+
+#[cfg(test)]
+mod tests {
+    use std::backtrace::BacktraceStatus;
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn new_and_message() {
+        let msg = "test error message";
+        let error = PacketDecodeError::new(msg, None);
+
+        assert_eq!(error.message(), msg);
+        assert_eq!(error.kind(), ErrorKind::Decode);
+    }
+
+    #[test]
+    fn with_kind_tags_the_error_with_the_given_kind() {
+        let error = PacketDecodeError::with_kind(ErrorKind::VerifyChecksum, "bad checksum", None);
+
+        assert_eq!(error.kind(), ErrorKind::VerifyChecksum);
+        assert_eq!(error.message(), "bad checksum");
+    }
+
+    #[test]
+    fn display_contains_message_and_stack_trace() {
+        let msg = "display error message";
+        let error = PacketDecodeError::new(msg, None);
+        let error_string = format!("{}", error);
+
+        assert!(error_string.contains(msg));
+        assert!(error_string.contains("Stack trace:"));
+    }
+
+    #[test]
+    fn error_trait_impl_exposes_backtrace_via_downcast() {
+        let msg = "trait impl error message";
+        let error: Box<dyn Error> = Box::new(PacketDecodeError::new(msg, None));
+
+        let backtraceable = error
+            .downcast_ref::<PacketDecodeError>()
+            .expect("should downcast to PacketDecodeError");
+        assert_eq!(
+            error.to_string(),
+            format!("{}\nStack trace:\n{:?}", msg, backtraceable.backtrace()),
+        );
+    }
+
+    #[test]
+    fn new_reuses_a_captured_sources_backtrace_instead_of_capturing_fresh() {
+        let mut inner = PacketDecodeError::new("inner", None);
+        inner.0.stack_trace = Backtrace::force_capture();
+        let inner_trace_text = format!("{:?}", inner.0.stack_trace);
+
+        let outer = PacketDecodeError::new("outer", Some(Box::new(inner)));
+
+        assert_eq!(format!("{:?}", outer.0.stack_trace), inner_trace_text);
+
+        let reused_source = outer
+            .source()
+            .unwrap()
+            .downcast_ref::<PacketDecodeError>()
+            .unwrap();
+        assert_eq!(reused_source.0.stack_trace.status(), BacktraceStatus::Disabled);
+    }
+
+    #[test]
+    fn context_wraps_the_original_error_as_its_source_and_keeps_its_kind() {
+        let inner =
+            PacketDecodeError::with_kind(ErrorKind::VerifyChecksum, "invalid option length", None);
+        let outer = inner.context("failed to decode TCP layer");
+
+        assert_eq!(outer.message(), "failed to decode TCP layer");
+        assert_eq!(outer.kind(), ErrorKind::VerifyChecksum);
+        let source = outer
+            .source()
+            .expect("context should set the original error as source");
+        assert_eq!(source.to_string().lines().next(), Some("invalid option length"));
+    }
+
+    #[test]
+    fn with_backtrace_style_off_skips_capturing_a_backtrace() {
+        let error = PacketDecodeError::with_backtrace_style(
+            ErrorKind::Decode,
+            "recover decode error",
+            None,
+            BacktraceStyle::Off,
+        );
+
+        assert_eq!(error.backtrace().status(), BacktraceStatus::Disabled);
+    }
+
+    #[test]
+    fn with_backtrace_style_full_force_captures_even_without_rust_backtrace() {
+        let error = PacketDecodeError::with_backtrace_style(
+            ErrorKind::Decode,
+            "recover decode error",
+            None,
+            BacktraceStyle::Full,
+        );
+
+        assert_eq!(error.backtrace().status(), BacktraceStatus::Captured);
+    }
+
+    #[test]
+    fn with_backtrace_style_off_still_reuses_a_sources_already_captured_backtrace() {
+        let mut inner = PacketDecodeError::new("inner", None);
+        inner.0.stack_trace = Backtrace::force_capture();
+        let inner_trace_text = format!("{:?}", inner.0.stack_trace);
+
+        let outer = PacketDecodeError::with_backtrace_style(
+            ErrorKind::Decode,
+            "outer",
+            Some(Box::new(inner)),
+            BacktraceStyle::Off,
+        );
+
+        assert_eq!(format!("{:?}", outer.0.stack_trace), inner_trace_text);
+    }
+
+    #[test]
+    fn context_preserves_the_original_backtrace_instead_of_capturing_a_fresh_one() {
+        let inner = PacketDecodeError::new("inner", None);
+        let inner_trace = format!("{:?}", inner.0.stack_trace);
+
+        let outer = inner.context("outer");
+
+        assert_eq!(format!("{:?}", outer.0.stack_trace), inner_trace);
+    }
+
+    #[test]
+    fn chain_walks_from_self_through_every_source() {
+        let root = PacketDecodeError::new("unexpected EOF", None);
+        let middle = root.context("invalid option length");
+        let outer = middle.context("failed to decode TCP layer");
+
+        let messages: Vec<String> = outer
+            .chain()
+            .map(|e| e.to_string().lines().next().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "failed to decode TCP layer".to_string(),
+                "invalid option length".to_string(),
+                "unexpected EOF".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn root_cause_returns_the_innermost_error() {
+        let root = PacketDecodeError::new("unexpected EOF", None);
+        let outer = root
+            .context("invalid option length")
+            .context("failed to decode TCP layer");
+
+        assert_eq!(
+            outer.root_cause().to_string().lines().next(),
+            Some("unexpected EOF")
+        );
+    }
+
+    #[test]
+    fn decode_context_trait_attaches_context_to_a_result() {
+        let result: Result<(), PacketDecodeError> = Err(PacketDecodeError::new("unexpected EOF", None));
+        let result = result.context("invalid option length");
+
+        let err = result.unwrap_err();
+        assert_eq!(err.message(), "invalid option length");
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn decode_context_with_context_only_builds_message_lazily_on_error() {
+        let mut built = false;
+        let ok: Result<i32, PacketDecodeError> = Ok(42);
+        let ok = ok.with_context(|| {
+            built = true;
+            "should not be built".to_string()
+        });
+        assert_eq!(ok.unwrap(), 42);
+        assert!(!built, "with_context should not invoke its closure on success");
+
+        let err: Result<i32, PacketDecodeError> = Err(PacketDecodeError::new("unexpected EOF", None));
+        let err = err.with_context(|| "invalid option length".to_string());
+        assert_eq!(err.unwrap_err().message(), "invalid option length");
+    }
+
+    #[test]
+    fn new_error_has_an_empty_trail() {
+        let error = PacketDecodeError::new("unexpected EOF", None);
+        assert!(error.trail().is_empty());
+    }
+
+    #[test]
+    fn attach_context_keeps_frames_outermost_first() {
+        use crate::rtpacket::decode::decoder_builder;
+        use crate::rtpacket::layertype::LayerTypes::LayerTypePayload;
+
+        let layer = |name: &str| LayerType {
+            id: LayerTypePayload as crate::rtpacket::layertype::LayerTypeID,
+            name: name.to_owned(),
+            decoder: decoder_builder(LayerTypePayload),
+        };
+
+        // `attach_context` is called innermost-first as the error unwinds
+        // (IPv4 before Ethernet), but frames() should still read
+        // outermost-first.
+        let error = PacketDecodeError::new("invalid option length", None)
+            .attach_context(layer("IPv4"), 14, 46, "entered IPv4")
+            .attach_context(layer("Ethernet"), 0, 60, "entered Ethernet");
+
+        assert_eq!(error.trail().frames().len(), 2);
+        assert_eq!(error.trail().frames()[0].layer.name, "Ethernet");
+        assert_eq!(error.trail().frames()[1].layer.name, "IPv4");
+    }
+
+    #[test]
+    fn display_renders_the_context_chain_above_the_stack_trace_when_present() {
+        use crate::rtpacket::decode::decoder_builder;
+        use crate::rtpacket::layertype::LayerTypes::LayerTypePayload;
+
+        let layer = |name: &str| LayerType {
+            id: LayerTypePayload as crate::rtpacket::layertype::LayerTypeID,
+            name: name.to_owned(),
+            decoder: decoder_builder(LayerTypePayload),
+        };
+
+        let error = PacketDecodeError::new("invalid option length", None)
+            .attach_context(layer("TCP"), 34, 26, "invalid option length")
+            .attach_context(layer("IPv4"), 14, 46, "entered IPv4");
+
+        let rendered = error.to_string();
+        assert!(rendered.contains("invalid option length"));
+        assert!(rendered.contains("while decoding TCP -> while decoding IPv4"));
+        assert!(rendered.contains("Stack trace:"));
+        assert!(rendered.find("while decoding TCP").unwrap() < rendered.find("Stack trace:").unwrap());
+    }
+}
@@ -1,11 +1,12 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use std::rc::Rc;
-use std::sync::Mutex;
+use std::sync::{Arc, OnceLock, RwLock};
 
 use crate::rtpacket::decode::{DecodeFunc, PacketBuilder};
-use crate::rtpacket::error::decodeerror::DecodeError;
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
 
 const ARRAY_REPEAT_VALUE: Option<LayerType> = None;
 
@@ -44,100 +45,333 @@ pub enum LayerTypes {
     LayerTypeDecodeFailure = 1,
     LayerTypePayload = 2,
     LayerTypeFragment = 3,
+    LayerTypeDecompressedPayload = 4,
+    LayerTypeQUIC = 5,
 }
 
+/// Returned by `register_layer` when a layer type is already registered and
+/// `override_existing` was not set.
+#[derive(Debug)]
+pub struct LayerAlreadyRegisteredError {
+    pub id: LayerTypeID,
+}
+
+impl fmt::Display for LayerAlreadyRegisteredError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "layer type {} is already registered", self.id)
+    }
+}
+
+impl Error for LayerAlreadyRegisteredError {}
+
+/// The mutable state backing a `LayerRegistry`. Held behind a single
+/// `RwLock` so a `ReloadHandle` can swap a decoder in place without racing
+/// `LayerType::decode` calls already in flight: readers take a read-lock for
+/// the duration of a single lookup, writers take a write-lock only while
+/// actually replacing an entry.
+struct RegistryState {
+    decoders_by_layer_name: HashMap<String, DecodeFunc>,
+    lt_meta_map: HashMap<LayerTypeID, LayerType>,
+    lt_meta: Vec<Option<LayerType>>,
+}
+
+impl RegistryState {
+    fn new() -> Self {
+        RegistryState {
+            decoders_by_layer_name: HashMap::new(),
+            lt_meta_map: HashMap::new(),
+            lt_meta: vec![ARRAY_REPEAT_VALUE; MAX_LAYER_TYPE],
+        }
+    }
+
+    fn get(&self, num: isize) -> Option<&LayerType> {
+        if (0..MAX_LAYER_TYPE as isize).contains(&num) {
+            self.lt_meta[num as usize].as_ref()
+        } else {
+            self.lt_meta_map.get(&(num as LayerTypeID))
+        }
+    }
+
+    fn insert(&mut self, num: isize, meta: LayerType) {
+        if (0..MAX_LAYER_TYPE as isize).contains(&num) {
+            self.lt_meta[num as usize] = Some(meta.clone());
+        } else {
+            self.lt_meta_map.insert(num as LayerTypeID, meta.clone());
+        }
+        self.decoders_by_layer_name.insert(meta.name.clone(), meta.decoder);
+    }
+}
+
+/// A registry of every known `LayerType`, keyed by `LayerTypeID`.
+///
+/// Holds its state behind an `Arc<RwLock<_>>` so a [`ReloadHandle`] obtained
+/// via [`LayerRegistry::reload_handle`] can keep swapping decoders in a
+/// running process, long after the `LayerRegistry` that created it has gone
+/// out of scope.
 pub struct LayerRegistry {
-    decoders_by_layer_name: Mutex<HashMap<String, DecodeFunc>>,
-    lt_meta_map: Mutex<HashMap<LayerTypeID, Option<LayerType>>>,
-    lt_meta: [Option<LayerType>; MAX_LAYER_TYPE],
+    state: Arc<RwLock<RegistryState>>,
 }
+
 impl LayerRegistry {
     pub fn new() -> Self {
         LayerRegistry {
-            decoders_by_layer_name: Mutex::new(HashMap::new()),
-            lt_meta_map: Mutex::new(HashMap::new()),
-            lt_meta: [ARRAY_REPEAT_VALUE; MAX_LAYER_TYPE], // Assuming MAX_LAYER_TYPE and LayerTypeMetadata are properly defined
+            state: Arc::new(RwLock::new(RegistryState::new())),
         }
-        //
-        // let layer_type_metadata = LayerType {
-        //     id: LayerTypeZero as LayerTypeID,
-        //     name: "Unknown".to_owned(),
-        //     decoder: Rc::from(create_decode_unknown()), // Adjust based on how decoders are implemented.
-        // };
-        // Self.register_layer(&layer_type_metadata, LayerTypeZero as isize)
-        //     .expect("could not add layer");
-        //
-        // let layer_type_metadata = LayerType {
-        //     id: LayerTypeDecodeFailure as LayerTypeID,
-        //     name: "DecodeFailure".to_owned(),
-        //     decoder: Rc::from(create_decode_unknown()), // Adjust based on how decoders are implemented.
-        // };
-        //
-        // Self.register_layer(&layer_type_metadata, LayerTypeDecodeFailure as isize)
-        //     .expect("could not add layer");
-        //
-        // let layer_type_metadata = LayerType {
-        //     id: LayerTypePayload as LayerTypeID,
-        //     name: "DecodePayload".to_owned(),
-        //     decoder: Rc::from(create_decode_payload()), // Adjust based on how decoders are implemented.
-        // };
-        //
-        // Self.register_layer(&layer_type_metadata, LayerTypePayload as isize)
-        //     .expect("could not add layer");
-        //
-        // let layer_type_metadata = LayerType {
-        //     id: LayerTypeFragment as LayerTypeID,
-        //     name: "DecodeFragment".to_owned(),
-        //     decoder: Rc::from(create_decode_fragment()), // Adjust based on how decoders are implemented.
-        // };
-        //
-        // Self.register_layer(&layer_type_metadata, LayerTypeFragment as isize)
-        //     .expect("could not add layer");
-    }
-
-    pub fn register_layer(&mut self, meta: &LayerType, num: isize) -> Result<(), Box<dyn Error>> {
-        let n_num = num as usize;
-        if 0 <= num && num < MAX_LAYER_TYPE as isize {
-            let lt_meta = &self.lt_meta;
-            if lt_meta[num as usize].is_some() {
-                panic!("Layer type already exists");
-            }
-        } else {
-            let mut lt_meta_map = self.lt_meta_map.lock().unwrap();
-            if lt_meta_map.contains_key(&n_num) {
-                let m = lt_meta_map.get_mut(&n_num);
-                if m.is_some() {
-                    panic!("Layer type already exists");
-                }
-            }
+    }
+
+    /// Returns a cheap, cloneable handle that can reload or modify decoders
+    /// for layer types already registered with this `LayerRegistry`, from
+    /// anywhere that handle is sent to, without restarting the program.
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle {
+            state: self.state.clone(),
         }
+    }
 
-        // Assuming the Decoder trait and a way to clone or reference it appropriately
-        self.override_layer_type(num, meta);
+    /// Registers `meta` under id `num`. If a layer type is already
+    /// registered at that id, this returns a `LayerAlreadyRegisteredError`
+    /// unless `override_existing` is set, in which case the existing
+    /// decoder/metadata is replaced in place.
+    pub fn register_layer(
+        &self,
+        meta: &LayerType,
+        num: isize,
+        override_existing: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut state = self.state.write().unwrap();
+        if !override_existing && state.get(num).is_some() {
+            return Err(Box::new(LayerAlreadyRegisteredError {
+                id: num as LayerTypeID,
+            }));
+        }
+        state.insert(num, meta.clone());
         Ok(())
     }
 
-    fn override_layer_type(&mut self, num: isize, meta: &LayerType) -> LayerTypeID {
-        if 0 <= num && num < MAX_LAYER_TYPE as isize {
-            // Directly override without checking if it already exists
-            self.lt_meta[num as usize] = Some(meta.clone());
-        } else {
-            // For numbers outside the predefined range, use a map.
-            // This avoids the "stupidity" comment regarding double lock by consolidating the locking operation.
-            let mut lt_meta_map = self.lt_meta_map.lock().unwrap();
-            lt_meta_map.insert(num as LayerTypeID, Some(meta.clone()));
+    /// Returns the currently registered `LayerType` for `id`, if any. This
+    /// is what the decode path should call to look up a fresh snapshot of a
+    /// layer's decoder before invoking it, so in-flight reloads are picked
+    /// up by the next lookup rather than requiring a restart.
+    pub fn lookup(&self, id: LayerTypeID) -> Option<LayerType> {
+        self.state.read().unwrap().get(id as isize).cloned()
+    }
+}
+
+impl Default for LayerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn decoder_registry() -> &'static RwLock<HashMap<LayerTypeID, DecodeFunc>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<LayerTypeID, DecodeFunc>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Registers `decoder` as the process-wide entry point for decoding a
+/// layer's payload once it advertises `id` as its next layer type,
+/// overwriting any decoder already registered for that id. This lets
+/// `PacketBuilder::next_decoder_auto` dispatch to a protocol decoder by id
+/// alone, rather than requiring each caller to already know which
+/// `DecodeFunc` corresponds to it.
+pub fn register_decoder(id: LayerTypeID, decoder: DecodeFunc) {
+    decoder_registry().write().unwrap().insert(id, decoder);
+}
+
+/// Returns the decoder currently registered for `id`, if any.
+pub fn lookup_decoder(id: LayerTypeID) -> Option<DecodeFunc> {
+    decoder_registry().read().unwrap().get(&id).copied()
+}
+
+/// Registers the decoder for every layer type this crate knows about by
+/// default. Safe to call more than once; later calls simply re-register the
+/// same defaults, so any previous `register_decoder` override for one of
+/// these ids is lost.
+///
+/// This is the registry's only source for these four built-in mappings —
+/// `crate::rtpacket::decode::decoder_builder` only ever reads the registry,
+/// it doesn't know about any decoder by itself — so every built-in decoder
+/// is registered here directly rather than via `decoder_builder`.
+pub fn register_defaults() {
+    use crate::rtpacket::decode::decodecompressedpayload::decode_compressed_payload;
+    use crate::rtpacket::decode::decodefragment::decode_fragment;
+    use crate::rtpacket::decode::decodepayload::create_decode_payload;
+    use crate::rtpacket::decode::decodequic::decode_quic;
+    use crate::rtpacket::decode::decodeunknown::create_decode_unknown;
+    use LayerTypes::{
+        LayerTypeDecodeFailure, LayerTypeDecompressedPayload, LayerTypeFragment, LayerTypePayload,
+        LayerTypeQUIC, LayerTypeZero,
+    };
+
+    register_decoder(LayerTypeZero as LayerTypeID, create_decode_unknown);
+    register_decoder(LayerTypeDecodeFailure as LayerTypeID, create_decode_unknown);
+    register_decoder(LayerTypePayload as LayerTypeID, create_decode_payload);
+    register_decoder(LayerTypeFragment as LayerTypeID, decode_fragment);
+    register_decoder(LayerTypeDecompressedPayload as LayerTypeID, decode_compressed_payload);
+    register_decoder(LayerTypeQUIC as LayerTypeID, decode_quic);
+}
+
+/// A cheap, cloneable handle that can hot-reload decoders registered with a
+/// [`LayerRegistry`], so protocol dissectors can be patched or experimented
+/// with in a running process.
+///
+/// Every clone shares the same underlying `RwLock`, so a `reload`/`modify`
+/// call is visible to every other handle (and to the `LayerRegistry` itself)
+/// as soon as the write-lock is released.
+#[derive(Clone)]
+pub struct ReloadHandle {
+    state: Arc<RwLock<RegistryState>>,
+}
+
+impl ReloadHandle {
+    /// Replaces the metadata and decoder registered for `id` with
+    /// `new_meta`, regardless of what (if anything) was registered there
+    /// before. Readers that already hold a cloned `LayerType` from before
+    /// the swap keep using the old decoder; only subsequent `lookup` calls
+    /// observe `new_meta`.
+    pub fn reload(&self, id: LayerTypeID, new_meta: LayerType) {
+        self.state.write().unwrap().insert(id as isize, new_meta);
+    }
+
+    /// Applies `f` to the `LayerType` currently registered for `id`, if any,
+    /// and writes the result back in place. Returns `false` if `id` has no
+    /// registered layer type.
+    pub fn modify(&self, id: LayerTypeID, mut f: impl FnMut(&mut LayerType)) -> bool {
+        let mut state = self.state.write().unwrap();
+        let Some(mut current) = state.get(id as isize).cloned() else {
+            return false;
+        };
+        f(&mut current);
+        state.insert(id as isize, current);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rtpacket::decode::decodeunknown::create_decode_unknown;
+    use crate::rtpacket::error::packetdecodeerror::DecodeError;
+
+    use super::*;
+
+    fn decode_ok(_data: Rc<[u8]>, _builder: Rc<RefCell<dyn PacketBuilder>>) -> Result<(), DecodeError> {
+        Ok(())
+    }
+
+    fn test_layer(id: LayerTypeID, name: &str, decoder: DecodeFunc) -> LayerType {
+        LayerType {
+            id,
+            name: name.to_owned(),
+            decoder,
         }
+    }
+
+    #[test]
+    fn register_then_lookup_round_trips() {
+        let registry = LayerRegistry::new();
+        registry
+            .register_layer(&test_layer(7, "Test", decode_ok), 7, false)
+            .unwrap();
+
+        let found = registry.lookup(7).expect("layer type 7 should be registered");
+        assert_eq!(found.name, "Test");
+    }
 
-        // Insert or update the decoder associated with the given layer name.
-        // This operation is done outside of the if-else block to avoid repetition and potential errors.
-        let moved_meta = meta.clone();
-        self.decoders_by_layer_name
-            .lock()
-            .unwrap()
-            .insert(meta.name.clone(), moved_meta.decoder);
+    #[test]
+    fn register_twice_without_override_fails() {
+        let registry = LayerRegistry::new();
+        registry
+            .register_layer(&test_layer(7, "Test", decode_ok), 7, false)
+            .unwrap();
 
-        num as LayerTypeID
+        let err = registry
+            .register_layer(&test_layer(7, "TestAgain", decode_ok), 7, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("already registered"));
     }
 
-    // Methods to add, retrieve, and manage decoders and metadata would follow...
+    #[test]
+    fn register_twice_with_override_replaces_entry() {
+        let registry = LayerRegistry::new();
+        registry
+            .register_layer(&test_layer(7, "Test", create_decode_unknown), 7, false)
+            .unwrap();
+        registry
+            .register_layer(&test_layer(7, "TestReplaced", decode_ok), 7, true)
+            .unwrap();
+
+        let found = registry.lookup(7).unwrap();
+        assert_eq!(found.name, "TestReplaced");
+    }
+
+    #[test]
+    fn reload_handle_swaps_decoder_visible_to_new_lookups() {
+        let registry = LayerRegistry::new();
+        registry
+            .register_layer(&test_layer(9, "Original", create_decode_unknown), 9, false)
+            .unwrap();
+
+        let handle = registry.reload_handle();
+        handle.reload(9, test_layer(9, "Patched", decode_ok));
+
+        let found = registry.lookup(9).unwrap();
+        assert_eq!(found.name, "Patched");
+        assert!(found.decode(Rc::from(&[][..]), mock_builder()).is_ok());
+    }
+
+    #[test]
+    fn modify_mutates_the_registered_layer_type_in_place() {
+        let registry = LayerRegistry::new();
+        registry
+            .register_layer(&test_layer(11, "Original", decode_ok), 11, false)
+            .unwrap();
+
+        let handle = registry.reload_handle();
+        let modified = handle.modify(11, |lt| lt.name = "Renamed".to_owned());
+
+        assert!(modified);
+        assert_eq!(registry.lookup(11).unwrap().name, "Renamed");
+    }
+
+    #[test]
+    fn modify_on_unregistered_id_returns_false() {
+        let registry = LayerRegistry::new();
+        let handle = registry.reload_handle();
+        assert!(!handle.modify(42, |lt| lt.name = "Ghost".to_owned()));
+    }
+
+    fn mock_builder() -> Rc<RefCell<dyn PacketBuilder>> {
+        use crate::rtpacket::decode::packetbuilder::MockPacketBuilder;
+
+        Rc::new(RefCell::new(MockPacketBuilder {
+            layers_added: vec![],
+            link_layer: None,
+            application_layer: None,
+        }))
+    }
+
+    // `decoder_registry()` is a process-wide global, so both checks live in
+    // a single test to avoid racing with other `#[test]` threads over the
+    // same ids.
+    #[test]
+    fn register_defaults_then_override_round_trips() {
+        register_defaults();
+        for id in [
+            LayerTypes::LayerTypeZero as LayerTypeID,
+            LayerTypes::LayerTypeDecodeFailure as LayerTypeID,
+            LayerTypes::LayerTypePayload as LayerTypeID,
+            LayerTypes::LayerTypeFragment as LayerTypeID,
+            LayerTypes::LayerTypeDecompressedPayload as LayerTypeID,
+            LayerTypes::LayerTypeQUIC as LayerTypeID,
+        ] {
+            assert!(lookup_decoder(id).is_some());
+        }
+
+        const TEST_ID: LayerTypeID = 9001;
+        assert!(lookup_decoder(TEST_ID).is_none());
+
+        register_decoder(TEST_ID, decode_ok);
+        let decoder = lookup_decoder(TEST_ID).expect("should be registered");
+        assert!(decoder(Rc::from(&[][..]), mock_builder()).is_ok());
+    }
 }
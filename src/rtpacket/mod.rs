@@ -1,6 +1,7 @@
 pub(crate) mod base;
 pub mod capture;
 pub mod checksum;
+pub mod encap;
 pub mod error;
 pub mod layertype;
 
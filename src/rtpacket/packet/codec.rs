@@ -0,0 +1,260 @@
+use std::cell::RefCell;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+
+use bytes::{Buf, BytesMut};
+use futures::Stream;
+use tokio::io::AsyncRead;
+use tokio_util::codec::{Decoder, FramedRead};
+
+use crate::rtpacket::decode::{DecodeFunc, PacketBuilder};
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
+use crate::rtpacket::error::ErrorDecodeable;
+use crate::rtpacket::packet::decodeoptions::DecodeOptions;
+use crate::rtpacket::packet::eagerpacket::EagerPacket;
+use crate::rtpacket::packet::Packetable;
+
+/// Number of bytes in the length prefix that frames each packet record.
+const LENGTH_PREFIX_BYTES: usize = 4;
+
+/// Largest length a `LengthDelimited` prefix is allowed to claim, rejected
+/// before it's used to size a `BytesMut` reservation. Generous enough for
+/// any jumbo-frame capture, but well short of a corrupted or hostile prefix
+/// forcing a multi-gigabyte allocation (mirrors the same guard in
+/// [`crate::rtpacket::packet::streamdecoder::Decoder`] and
+/// [`crate::rtpacket::capture::pcap`]).
+const MAX_FRAME_LENGTH: usize = 1 << 20;
+
+/// Controls how `PacketCodec::decode` finds the boundary of the next record
+/// in the buffer `FramedRead`/`FramedWrite` hand it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameMode {
+    /// A 4-byte big-endian length prefix precedes each record. Right for
+    /// stream transports (a TCP stream, pipe, or file) where one `read`
+    /// can return a partial record, several records, or anything between.
+    LengthDelimited,
+    /// The entire buffer handed to one `decode` call is a single record,
+    /// with no framing of its own. Right only for transports that
+    /// themselves guarantee exactly one packet per read, e.g. a UDP socket
+    /// driven through `tokio_util::udp::UdpFramed`. Not a fit for a
+    /// plain `AsyncRead` over a file or pipe, where an arbitrary number of
+    /// records' bytes can land in a single read and would be merged into
+    /// one oversized "packet".
+    WholeBuffer,
+}
+
+/// A `tokio_util::codec::Decoder` that turns a byte stream of packet records
+/// into decoded packets.
+///
+/// Framing is controlled by [`FrameMode`]: [`PacketCodec::new`] assumes a
+/// 4-byte big-endian length prefix precedes each record, the async
+/// equivalent of the `(Vec<u8>, CaptureInfo)` pairs a blocking
+/// `PacketSource::source` closure returns one at a time.
+/// [`PacketCodec::datagram`] instead treats every `decode` call's buffer as
+/// one complete record, for transports that already deliver one packet per
+/// read. Either way, `decode` runs the frame through `decoder` and builds
+/// the assembled packet, so async consumers get a clean `next().await`
+/// packet stream instead of manually driving the decode functions.
+///
+/// Use [`PacketStream`] rather than this type directly if you want a flat
+/// `futures::Stream<Item = Result<Box<dyn Packetable>, DecodeError>>`.
+pub struct PacketCodec {
+    options: DecodeOptions,
+    decoder: DecodeFunc,
+    interface_index: usize,
+    frame_mode: FrameMode,
+}
+
+impl PacketCodec {
+    /// Creates a new `PacketCodec` that decodes each length-prefixed record
+    /// with `decoder`, carrying `options` (lazy/no_copy/pool/...) into every
+    /// packet it builds.
+    pub fn new(decoder: DecodeFunc, options: DecodeOptions) -> Self {
+        PacketCodec {
+            options,
+            decoder,
+            interface_index: 0,
+            frame_mode: FrameMode::LengthDelimited,
+        }
+    }
+
+    /// Creates a `PacketCodec` that treats every `decode` call's buffer as
+    /// one complete, unframed record, for datagram transports (e.g. a UDP
+    /// socket via `UdpFramed`) where the underlying I/O already delivers
+    /// exactly one packet per read.
+    pub fn datagram(decoder: DecodeFunc, options: DecodeOptions) -> Self {
+        PacketCodec {
+            options,
+            decoder,
+            interface_index: 0,
+            frame_mode: FrameMode::WholeBuffer,
+        }
+    }
+
+    /// Sets the interface index recorded in every packet's metadata,
+    /// mirroring `CaptureInfo::interface_index`.
+    pub fn with_interface_index(mut self, interface_index: usize) -> Self {
+        self.interface_index = interface_index;
+        self
+    }
+
+    /// Pulls the next complete frame's bytes out of `src` according to
+    /// `self.frame_mode`. Returns `Ok(None)` if `src` doesn't yet hold a
+    /// complete frame, or an error if a `LengthDelimited` prefix claims more
+    /// than `MAX_FRAME_LENGTH` bytes.
+    fn next_frame(&self, src: &mut BytesMut) -> Result<Option<Rc<[u8]>>, io::Error> {
+        match self.frame_mode {
+            FrameMode::LengthDelimited => {
+                if src.len() < LENGTH_PREFIX_BYTES {
+                    return Ok(None);
+                }
+
+                let length = u32::from_be_bytes(src[..LENGTH_PREFIX_BYTES].try_into().unwrap()) as usize;
+
+                if length > MAX_FRAME_LENGTH {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "frame length prefix claims {length} bytes, which exceeds the \
+                             {MAX_FRAME_LENGTH} byte limit"
+                        ),
+                    ));
+                }
+
+                if src.len() < LENGTH_PREFIX_BYTES + length {
+                    src.reserve(LENGTH_PREFIX_BYTES + length - src.len());
+                    return Ok(None);
+                }
+
+                src.advance(LENGTH_PREFIX_BYTES);
+                Ok(Some(Rc::from(src.split_to(length).as_ref())))
+            }
+            FrameMode::WholeBuffer => {
+                if src.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(Rc::from(src.split().as_ref())))
+            }
+        }
+    }
+}
+
+impl Decoder for PacketCodec {
+    type Item = Result<Box<dyn Packetable>, DecodeError>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(data) = self.next_frame(src)? else {
+            return Ok(None);
+        };
+
+        let mut packet = EagerPacket::new(data.clone(), self.options);
+        packet.metadata.timestamp = SystemTime::now();
+        packet.metadata.capture_length = data.len();
+        packet.metadata.length = data.len();
+        packet.metadata.interface_index = self.interface_index;
+
+        // Keep a concrete handle so we can reclaim the `EagerPacket` after
+        // decoding, while the decoder itself only ever sees the `dyn
+        // PacketBuilder`/`DecodeFeedback` trait objects it's written against.
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+
+        let decode_result = (self.decoder)(data, builder);
+
+        let item = match decode_result {
+            Ok(()) => {
+                let packet = Rc::try_unwrap(packet_handle)
+                    .unwrap_or_else(|_| panic!("decoder retained a handle past its call"))
+                    .into_inner();
+                Ok(Box::new(packet) as Box<dyn Packetable>)
+            }
+            Err(err) => Err(err),
+        };
+
+        Ok(Some(item))
+    }
+}
+
+/// A `futures::Stream` of decoded packets read from an `AsyncRead` byte
+/// source, e.g. a TCP stream, file, or pipe.
+///
+/// This flattens `FramedRead`'s `Result<Result<Box<dyn Packetable>,
+/// DecodeError>, io::Error>` items (one `Result` for framing I/O, one for
+/// decode failure) into a single `Result<Box<dyn Packetable>, DecodeError>`,
+/// wrapping I/O errors as a `DecodeError` so callers only deal with one
+/// error type, just as they would reading from a blocking `PacketSource`.
+pub struct PacketStream<R> {
+    framed: FramedRead<R, PacketCodec>,
+}
+
+impl<R: AsyncRead + Unpin> PacketStream<R> {
+    /// Creates a `PacketStream` that reads packet records from `source` and
+    /// decodes each with `decoder`, carrying `options` into every packet.
+    pub fn new(source: R, decoder: DecodeFunc, options: DecodeOptions) -> Self {
+        PacketStream {
+            framed: FramedRead::new(source, PacketCodec::new(decoder, options)),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for PacketStream<R> {
+    type Item = Result<Box<dyn Packetable>, DecodeError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match Pin::new(&mut self.framed).poll_next(cx) {
+            Poll::Ready(Some(Ok(item))) => Poll::Ready(Some(item)),
+            Poll::Ready(Some(Err(io_err))) => Poll::Ready(Some(Err(DecodeError::new(
+                &format!("packet stream I/O error: {}", io_err),
+                Some(Box::new(io_err)),
+            )))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_noop(_data: Rc<[u8]>, _builder: Rc<RefCell<dyn PacketBuilder>>) -> Result<(), DecodeError> {
+        Ok(())
+    }
+
+    #[test]
+    fn datagram_codec_treats_the_whole_buffer_as_one_record() {
+        let mut codec = PacketCodec::datagram(decode_noop, DecodeOptions::default());
+        let mut src = BytesMut::from(&[1u8, 2, 3][..]);
+
+        let item = codec
+            .decode(&mut src)
+            .expect("decode should not error")
+            .expect("a full buffer should always yield a record");
+
+        let packet = item.expect("decode_noop never fails");
+        assert_eq!(packet.data().as_ref(), &[1, 2, 3]);
+        assert!(src.is_empty(), "datagram mode should consume the entire buffer");
+    }
+
+    #[test]
+    fn datagram_codec_returns_none_for_an_empty_buffer() {
+        let mut codec = PacketCodec::datagram(decode_noop, DecodeOptions::default());
+        let mut src = BytesMut::new();
+
+        assert!(codec.decode(&mut src).expect("decode should not error").is_none());
+    }
+
+    #[test]
+    fn length_delimited_codec_rejects_a_prefix_claiming_an_oversized_frame() {
+        let mut codec = PacketCodec::new(decode_noop, DecodeOptions::default());
+        let mut src = BytesMut::from(&(MAX_FRAME_LENGTH as u32 + 1).to_be_bytes()[..]);
+
+        codec
+            .decode(&mut src)
+            .expect_err("a length prefix over MAX_FRAME_LENGTH should be rejected before reserving");
+    }
+}
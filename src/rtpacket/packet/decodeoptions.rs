@@ -1,3 +1,5 @@
+use crate::rtpacket::error::BacktraceStyle;
+
 /// `DecodeOptions` configures how to decode a packet.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct DecodeOptions {
@@ -28,6 +30,86 @@ pub struct DecodeOptions {
     /// This is disabled by default because the reassembly package drives the decoding
     /// of TCP payload data after reassembly.
     pub decode_streams_as_datagrams: bool,
+    /// Enables `decode_compressed_payload` to inflate gzip/deflate/brotli
+    /// application-layer payloads into a `DecompressedLayer` and resume
+    /// decoding from the inflated bytes. Disabled by default, so existing
+    /// callers keep seeing raw, still-encoded payload bytes exactly as
+    /// before.
+    pub decompress_payloads: bool,
+    /// Hints `decode_compressed_payload` that an as-yet-undecoded payload is
+    /// brotli-compressed. Unlike gzip (`1f 8b`) and deflate (the zlib
+    /// header), brotli has no magic bytes to sniff, so this must be set by
+    /// the caller when they already know the content encoding (e.g. from an
+    /// HTTP `Content-Encoding: br` header). Ignored unless
+    /// `decompress_payloads` is also set.
+    pub assume_brotli: bool,
+    /// The destination connection ID length `decode_quic` should assume for
+    /// a QUIC short header. Short headers carry no length of their own (RFC
+    /// 9000 section 17.3), so a caller decoding 1-RTT packets must supply
+    /// whatever length it negotiated for the connection during its Initial
+    /// exchange. `None` (the default) means short-header QUIC packets can't
+    /// be decoded; long headers, which carry explicit connection ID lengths,
+    /// are unaffected.
+    pub quic_short_header_dcid_len: Option<u8>,
+    /// Enables push-based decoding via
+    /// [`crate::rtpacket::packet::streamingdecoder::StreamingDecoder`], which
+    /// accumulates bytes across separate `push` calls instead of requiring
+    /// the full packet up front. Carried on `DecodeOptions` purely as a
+    /// descriptive flag — `StreamingDecoder::new` takes `DecodeOptions`
+    /// directly and doesn't read this field back, the same way
+    /// `decode_streams_as_datagrams` documents TCP reassembly's behavior
+    /// without a decoder consulting it itself.
+    pub streaming: bool,
+    /// Enables `EagerPacket` to accumulate a `Stats` of decode metrics
+    /// (layers decoded, bytes per layer type, `DecodeFailure` count, panics
+    /// recovered, pool hits/misses) as the packet is built, retrievable
+    /// afterwards via `EagerPacket::stats`. Disabled by default so packets
+    /// built without profiling in mind don't pay for the bookkeeping.
+    pub collect_stats: bool,
+    /// Controls whether (and how) `EagerPacket::recover_decode_error` — the
+    /// panic-recovery path that builds the `DecodeFailure` layer — captures
+    /// a backtrace for the resulting error. Defaults to
+    /// `BacktraceStyle::Short`, this crate's previous, implicit behavior of
+    /// always calling `Backtrace::capture()` (which itself only collects
+    /// frames if `RUST_BACKTRACE`/`RUST_LIB_BACKTRACE` is set). Set to `Off`
+    /// to skip capture entirely on a hot recovery path that never inspects
+    /// it, or to `Full` to force capture regardless of the environment.
+    pub backtrace_style: BacktraceStyle,
+    /// Stops decoding and fails with a descriptive `DecodeError` once this
+    /// many layers have been added, rather than continuing to recurse.
+    /// Defeats deeply nested or looping encapsulation in a crafted packet.
+    /// `None` (the default) means unlimited, matching this crate's previous
+    /// behavior.
+    pub max_layers: Option<usize>,
+    /// Caps the total bytes a single packet's layer decoders may claim
+    /// (summed across every decoded layer's contents), failing with a
+    /// descriptive `DecodeError` once the running total reaches this limit.
+    /// Bounds amplification from decoders that can produce more bytes than
+    /// they were given, e.g. `decode_compressed_payload` under
+    /// `decompress_payloads`. `None` (the default) means unlimited.
+    pub max_decoded_bytes: Option<usize>,
+    /// Caps the input payload handed to any single layer decoder, failing
+    /// with a descriptive `DecodeError` if the next layer's payload would
+    /// exceed it. This is a proxy for "what a layer may allocate/copy" —
+    /// this crate has no generic way to measure a decoder's actual
+    /// allocations. `None` (the default) means unlimited.
+    pub max_alloc_per_layer: Option<usize>,
+    /// Instead of aborting the whole decode at the first layer failure,
+    /// stash each one as a
+    /// [`crate::rtpacket::error::DecodeErrorRecord`] on
+    /// `EagerPacket::decode_errors` and let decoding of the layers already
+    /// produced stand. Disabled by default, matching this crate's previous
+    /// behavior of surfacing only the single terminal `DecodeFailure` layer.
+    ///
+    /// `next_decoder` hands each registered decoder a *clone* of the
+    /// builder, and merges that clone's state (including any stashed
+    /// `DecodeErrorRecord`s) back into the caller's builder once the
+    /// decoder returns. This happens at every level of recursion, so a
+    /// decoder that itself calls `next_decoder` on the builder it was
+    /// handed has its nested failures fold back out to whichever
+    /// `EagerPacket` the original caller holds, no matter how many
+    /// `next_decoder` calls deep the failure occurred.
+    pub accumulate_errors: bool,
 }
 
 // Usage:
@@ -35,6 +117,7 @@ pub struct DecodeOptions {
 // let lazy_options = DecodeOptions::lazy();
 // let no_copy_options = DecodeOptions::no_copy();
 // let datagram_options = DecodeOptions::decode_streams_as_datagrams();
+// let decompress_options = DecodeOptions::decompress_payloads();
 impl DecodeOptions {
     pub const DEFAULT: DecodeOptions = DecodeOptions {
         lazy: false,
@@ -42,6 +125,16 @@ impl DecodeOptions {
         pool: false,
         skip_decode_recovery: false,
         decode_streams_as_datagrams: false,
+        decompress_payloads: false,
+        assume_brotli: false,
+        quic_short_header_dcid_len: None,
+        streaming: false,
+        collect_stats: false,
+        backtrace_style: BacktraceStyle::Short,
+        max_layers: None,
+        max_decoded_bytes: None,
+        max_alloc_per_layer: None,
+        accumulate_errors: false,
     };
 
     /// Provides the default DecodeOptions, which is the safest but slowest configuration.
@@ -52,6 +145,32 @@ impl DecodeOptions {
             pool: false,
             skip_decode_recovery: false,
             decode_streams_as_datagrams: false,
+            decompress_payloads: false,
+            assume_brotli: false,
+            quic_short_header_dcid_len: None,
+            streaming: false,
+            collect_stats: false,
+            backtrace_style: BacktraceStyle::Short,
+            max_layers: None,
+            max_decoded_bytes: None,
+            max_alloc_per_layer: None,
+            accumulate_errors: false,
+        }
+    }
+
+    /// Provides a conservative `DecodeOptions` configuration for decoding
+    /// untrusted traffic: caps layer nesting, total decoded bytes, and any
+    /// single layer's input payload, in addition to the usual defaults.
+    /// Callers with different traffic characteristics (e.g. legitimately
+    /// deep tunneling, or payloads larger than 1 MiB) should start from
+    /// this and override the individual `max_*` fields via struct-update
+    /// syntax rather than relying on these exact numbers.
+    pub fn hardened() -> Self {
+        DecodeOptions {
+            max_layers: Some(64),
+            max_decoded_bytes: Some(1 << 20),
+            max_alloc_per_layer: Some(64 * 1024),
+            ..Self::default()
         }
     }
 
@@ -78,6 +197,38 @@ impl DecodeOptions {
             ..Self::default()
         }
     }
+
+    /// Provides a DecodeOptions configuration with decompress_payloads enabled.
+    pub fn decompress_payloads() -> Self {
+        DecodeOptions {
+            decompress_payloads: true,
+            ..Self::default()
+        }
+    }
+
+    /// Provides a DecodeOptions configuration with streaming enabled.
+    pub fn streaming() -> Self {
+        DecodeOptions {
+            streaming: true,
+            ..Self::default()
+        }
+    }
+
+    /// Provides a DecodeOptions configuration with collect_stats enabled.
+    pub fn collect_stats() -> Self {
+        DecodeOptions {
+            collect_stats: true,
+            ..Self::default()
+        }
+    }
+
+    /// Provides a DecodeOptions configuration with accumulate_errors enabled.
+    pub fn accumulate_errors() -> Self {
+        DecodeOptions {
+            accumulate_errors: true,
+            ..Self::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -98,6 +249,111 @@ mod tests {
             !options.decode_streams_as_datagrams,
             "Default should not decode streams as datagrams."
         );
+        assert!(
+            !options.decompress_payloads,
+            "Default should not decompress payloads."
+        );
+        assert!(!options.assume_brotli, "Default should not assume brotli.");
+        assert_eq!(
+            options.quic_short_header_dcid_len, None,
+            "Default should have no QUIC short header destination connection ID length."
+        );
+        assert!(!options.streaming, "Default should not be streaming.");
+        assert!(
+            !options.collect_stats,
+            "Default should not collect stats."
+        );
+        assert_eq!(
+            options.backtrace_style,
+            BacktraceStyle::Short,
+            "Default should capture backtraces the same way this crate always has."
+        );
+        assert_eq!(options.max_layers, None, "Default should not limit layer count.");
+        assert_eq!(
+            options.max_decoded_bytes, None,
+            "Default should not limit total decoded bytes."
+        );
+        assert_eq!(
+            options.max_alloc_per_layer, None,
+            "Default should not limit a single layer's payload."
+        );
+        assert!(
+            !options.accumulate_errors,
+            "Default should surface only the single terminal decode failure."
+        );
+    }
+
+    #[test]
+    fn test_hardened_options() {
+        let options = DecodeOptions::hardened();
+        assert_eq!(options.max_layers, Some(64));
+        assert_eq!(options.max_decoded_bytes, Some(1 << 20));
+        assert_eq!(options.max_alloc_per_layer, Some(64 * 1024));
+        assert!(
+            !options.collect_stats,
+            "hardened options shouldn't implicitly enable unrelated features."
+        );
+    }
+
+    #[test]
+    fn test_backtrace_style_is_not_exposed_via_a_dedicated_constructor() {
+        // `backtrace_style` picks among three values rather than toggling a
+        // single flag, so (like `quic_short_header_dcid_len` and
+        // `assume_brotli`) it's set via struct-update syntax instead of a
+        // `DecodeOptions::backtrace_style()` constructor.
+        let options = DecodeOptions {
+            backtrace_style: BacktraceStyle::Off,
+            ..DecodeOptions::default()
+        };
+        assert_eq!(options.backtrace_style, BacktraceStyle::Off);
+    }
+
+    #[test]
+    fn test_collect_stats_options() {
+        let options = DecodeOptions::collect_stats();
+        assert!(options.collect_stats, "collect_stats options should collect stats.");
+        assert!(!options.lazy, "collect_stats options should not be lazy by default.");
+    }
+
+    #[test]
+    fn test_accumulate_errors_options() {
+        let options = DecodeOptions::accumulate_errors();
+        assert!(
+            options.accumulate_errors,
+            "accumulate_errors options should accumulate errors."
+        );
+        assert!(
+            !options.lazy,
+            "accumulate_errors options should not be lazy by default."
+        );
+    }
+
+    #[test]
+    fn test_streaming_options() {
+        let options = DecodeOptions::streaming();
+        assert!(options.streaming, "Streaming options should be streaming.");
+        assert!(!options.lazy, "Streaming options should not be lazy by default.");
+        assert!(
+            !options.no_copy,
+            "Streaming options should not be no_copy by default."
+        );
+    }
+
+    #[test]
+    fn test_decompress_payloads_options() {
+        let options = DecodeOptions::decompress_payloads();
+        assert!(
+            options.decompress_payloads,
+            "decompress_payloads options should decompress payloads."
+        );
+        assert!(
+            !options.assume_brotli,
+            "decompress_payloads options should not assume brotli by default."
+        );
+        assert!(
+            !options.lazy,
+            "decompress_payloads options should not be lazy by default."
+        );
     }
 
     #[test]
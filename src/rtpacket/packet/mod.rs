@@ -11,14 +11,18 @@ use crate::rtpacket::decode::DecodeFunc;
 
 use super::capture::CaptureInfo;
 use super::packet::decodeoptions::DecodeOptions;
-use super::packet::packetable::{Packet, Packetable};
+pub(crate) use super::packet::packetable::{Packet, Packetable};
+use crate::rtpacket::packet::stats::Stats;
 
+pub mod codec;
 pub mod decodeoptions;
-mod eagerpacket;
+pub(crate) mod eagerpacket;
 mod packetable;
-mod packetbase;
 mod packetdatasource;
 mod packetsource;
+pub mod stats;
+pub mod streamdecoder;
+pub mod streamingdecoder;
 mod zerocopy;
 
 const MAXIMUM_MTU: usize = 1500;
@@ -48,9 +52,34 @@ impl BytePool {
 
     // Get a Vec<u8> from the pool or create a new one if the pool is empty.
     pub fn get(&self) -> Vec<u8> {
+        self.get_with_stats(None)
+    }
+
+    /// Like `get`, but records a hit (a buffer was reused) or a miss (a new
+    /// one had to be allocated) on `stats`, if collecting is enabled via
+    /// `DecodeOptions::collect_stats`.
+    ///
+    /// Nothing in this crate calls this automatically yet: `new_packet`'s
+    /// `pool`-backed path is still a `todo!()` stub, so there's no live
+    /// decode call site to thread a packet's `Stats` through. Callers who
+    /// manage their own `BytePool` (as `PooledPacket` is meant to) can call
+    /// this directly in the meantime.
+    pub fn get_with_stats(&self, stats: Option<&mut Stats>) -> Vec<u8> {
         let mut pool = self.pool.lock().unwrap();
-        pool.pop()
-            .unwrap_or_else(|| Vec::with_capacity(self.capacity))
+        match pool.pop() {
+            Some(vec) => {
+                if let Some(stats) = stats {
+                    stats.record_pool_hit();
+                }
+                vec
+            }
+            None => {
+                if let Some(stats) = stats {
+                    stats.record_pool_miss();
+                }
+                Vec::with_capacity(self.capacity)
+            }
+        }
     }
 
     // Return a Vec<u8> to the pool if it's not larger than the maximum capacity.
@@ -138,3 +167,57 @@ fn new_packet(
 ) -> Box<dyn Packetable> {
     todo!()
 }
+
+/// Returned by `new_packet_for_encap` when `encap` has no entry-point
+/// decoder registered (see `crate::rtpacket::encap::register_encap`).
+#[derive(Debug)]
+pub struct UnregisteredEncapError {
+    pub encap: crate::rtpacket::encap::EncapType,
+}
+
+impl std::fmt::Display for UnregisteredEncapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "no decoder registered for encapsulation {:?}; call register_encap or register_defaults first",
+            self.encap
+        )
+    }
+}
+
+impl Error for UnregisteredEncapError {}
+
+/// Decodes `data` as a packet captured with link-layer encapsulation
+/// `encap`, looking up the registered entry-point decoder for it instead of
+/// requiring the caller to already know which `DecodeFunc` a given capture's
+/// link type corresponds to. Callers reading a pcap file, for instance, can
+/// map the file's `DLT_*` header straight to an `EncapType` and decode every
+/// packet in it without threading a decoder through by hand.
+pub fn new_packet_for_encap(
+    data: &[u8],
+    encap: crate::rtpacket::encap::EncapType,
+    options: DecodeOptions,
+) -> Result<Box<dyn Packetable>, UnregisteredEncapError> {
+    let decoder = crate::rtpacket::encap::decoder_for_encap(encap).ok_or(UnregisteredEncapError { encap })?;
+    Ok(new_packet(data, decoder, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_with_stats_records_a_miss_then_a_hit() {
+        let pool = BytePool::new(16);
+        let mut stats = Stats::new();
+
+        let buf = pool.get_with_stats(Some(&mut stats));
+        assert_eq!(stats.pool_misses, 1);
+        assert_eq!(stats.pool_hits, 0);
+
+        pool.put(buf);
+        let _ = pool.get_with_stats(Some(&mut stats));
+        assert_eq!(stats.pool_misses, 1);
+        assert_eq!(stats.pool_hits, 1);
+    }
+}
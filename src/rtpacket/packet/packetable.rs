@@ -6,11 +6,12 @@ use crate::rtpacket::base::{
 };
 use crate::rtpacket::capture::PacketMetadata;
 use crate::rtpacket::checksum::ChecksumMismatch;
-use crate::rtpacket::error::decodeerror::DecodeError;
-use crate::rtpacket::error::verifychecksumerror::VerifyChecksumError;
+use crate::rtpacket::error::packetdecodeerror::{DecodeError, ErrorKind, PacketDecodeError, VerifyChecksumError};
+use crate::rtpacket::error::{DecodeErrorRecord, ErrorDecodeable};
 use crate::rtpacket::layerclass::LayerClass;
 use crate::rtpacket::layertype::LayerTypeID;
 use crate::rtpacket::packet::decodeoptions::DecodeOptions;
+use crate::rtpacket::packet::stats::Stats;
 
 /// Represents the primary object used by a packet processing library. Packets are created
 /// by a `Decoder`'s decode call. A packet consists of a set of data, which
@@ -62,6 +63,21 @@ pub(crate) trait Packetable: Debug {
     /// Returns packet metadata associated with this packet.
     fn metadata(&self) -> &PacketMetadata;
 
+    /// Returns decode metrics accumulated while this packet was built, if
+    /// `DecodeOptions::collect_stats` was set when it was decoded. Types
+    /// that don't track stats can rely on the default `None`.
+    fn stats(&self) -> Option<&Stats> {
+        None
+    }
+
+    /// Returns every decode error accumulated while this packet was built,
+    /// if `DecodeOptions::accumulate_errors` was set when it was decoded.
+    /// Types that don't accumulate errors (and, with the flag off, packets
+    /// that hit at most one failure) can rely on the default empty slice.
+    fn decode_errors(&self) -> &[DecodeErrorRecord] {
+        &[]
+    }
+
     /// Verifies the checksums of all layers in this packet that have one, and
     /// returns all found checksum mismatches.
     fn verify_checksums(&self) -> Result<Vec<ChecksumMismatch>, VerifyChecksumError>;
@@ -183,6 +199,51 @@ impl Debug for Packet {
     }
 }
 
+/// Serializes a decoded `Packet` as `{ "meta": ..., "layers": [...] }`, where
+/// `meta` mirrors `PacketMetadata` and each layer serializes its own decoded
+/// fields via `Layer::serialize_fields`. This is meant for exporting captures
+/// to JSON (or any other `serde` format) for tooling/dashboards; it's a
+/// structured alternative to the human-readable `string()`/`dump()` text.
+impl serde::Serialize for Packet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        #[derive(serde::Serialize)]
+        struct MetaFields {
+            timestamp_unix_secs: u64,
+            timestamp_unix_nanos: u32,
+            capture_length: usize,
+            length: usize,
+            interface_index: usize,
+            truncated: bool,
+        }
+
+        let since_epoch = self
+            .metadata
+            .timestamp
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let meta = MetaFields {
+            timestamp_unix_secs: since_epoch.as_secs(),
+            timestamp_unix_nanos: since_epoch.subsec_nanos(),
+            capture_length: self.metadata.capture_length,
+            length: self.metadata.length,
+            interface_index: self.metadata.interface_index,
+            truncated: self.metadata.truncated,
+        };
+        let layers: Vec<&dyn Layer> = self.layers.iter().map(|l| l.as_ref()).collect();
+
+        let mut state = serializer.serialize_struct("Packet", 2)?;
+        state.serialize_field("meta", &meta)?;
+        state.serialize_field("layers", &layers)?;
+        state.end()
+    }
+}
+
 impl Packetable for Packet {
     fn layers(&self) -> Vec<Rc<dyn Layer>> {
         todo!()
@@ -236,25 +297,40 @@ impl Packetable for Packet {
     }
 
     fn verify_checksums(&self) -> Result<Vec<ChecksumMismatch>, VerifyChecksumError> {
-        // let mut mismatches = Vec::new();
-        // for (i, layer) in self.layers.iter().enumerate() {
-        //     if let Layer::SomeChecksumLayer(ref lwc) = layer { // Assuming an enum variant for layers with checksums
-        //         match lwc.verify_checksum() {
-        //             Ok(res) if !res.valid => {
-        //                 mismatches.push(ChecksumMismatch {
-        //                     result: res,
-        //                     layer: layer.clone(), // Assuming clone is implemented or use a reference
-        //                     layer_index: i,
-        //                 });
-        //             }
-        //             Err(e) => return Err(vec![e]), // Simplified error handling
-        //             _ => {}
-        //         }
-        //     }
-        // }
-        //
-        // if mismatches.is_empty() { Ok(()) } else { Err(mismatches) }
-        todo!()
+        let mut mismatches: Vec<ChecksumMismatch> = Vec::new();
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let verifiable = match layer.as_checksum_verifiable() {
+                Some(verifiable) => verifiable,
+                None => continue,
+            };
+
+            match verifiable.verify_checksum() {
+                Ok(result) => {
+                    if !result.valid {
+                        mismatches.push(ChecksumMismatch {
+                            result,
+                            layer: layer.clone(),
+                            layer_index: i,
+                        });
+                    }
+                }
+                Err(err) => {
+                    return Err(PacketDecodeError::with_kind(
+                        ErrorKind::VerifyChecksum,
+                        &format!(
+                            "could not verify checksum for layer {} ({:?}): {}",
+                            i + 1,
+                            layer.layer_type(),
+                            err
+                        ),
+                        Some(Box::new(err)),
+                    ));
+                }
+            }
+        }
+
+        Ok(mismatches)
     }
 
     fn packet_string(&self) -> String {
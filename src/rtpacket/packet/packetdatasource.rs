@@ -1,4 +1,6 @@
+use std::cell::{Cell, RefCell};
 use std::error::Error;
+
 use crate::rtpacket::capture::CaptureInfo;
 
 pub trait PacketDataSource {
@@ -8,24 +10,218 @@ pub trait PacketDataSource {
     fn read_packet_data(&self) -> Result<(Vec<u8>, CaptureInfo), Box<dyn Error>>;
 }
 
-pub struct Concat(Vec<Box<dyn PacketDataSource>>);
+/// True if `err` is the `io::ErrorKind::UnexpectedEof` sentinel used throughout
+/// this module to signal a clean end of a source, as opposed to a real failure.
+fn is_eof(err: &(dyn Error + 'static)) -> bool {
+    err.downcast_ref::<std::io::Error>()
+        .map_or(false, |err| err.kind() == std::io::ErrorKind::UnexpectedEof)
+}
 
+fn eof_error() -> Box<dyn Error> {
+    Box::new(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "EOF"))
+}
+
+/// Chains several `PacketDataSource`s together, reading from the first until
+/// it reports EOF, then advancing to the next, and so on.
+pub struct Concat(RefCell<Vec<Box<dyn PacketDataSource>>>);
 
 impl Concat {
-    pub fn read_packet_data(&mut self) -> Result<(Vec<u8>, CaptureInfo), Box<dyn Error>> {
-        while !self.0.is_empty() {
-            match self.0[0].read_packet_data() {
+    pub fn new(sources: Vec<Box<dyn PacketDataSource>>) -> Self {
+        Concat(RefCell::new(sources))
+    }
+}
+
+impl PacketDataSource for Concat {
+    fn read_packet_data(&self) -> Result<(Vec<u8>, CaptureInfo), Box<dyn Error>> {
+        let mut sources = self.0.borrow_mut();
+        while !sources.is_empty() {
+            match sources[0].read_packet_data() {
                 Ok(data) => return Ok(data),
                 Err(e) => {
-                    if e.downcast_ref::<std::io::Error>().map_or(false, |err| err.kind() == std::io::ErrorKind::UnexpectedEof) {
-                        self.0.remove(0); // Remove the first element and continue if EOF
+                    if is_eof(&*e) {
+                        sources.remove(0); // Remove the first element and continue if EOF
                         continue;
                     }
                     return Err(e);
-                },
+                }
+            }
+        }
+        Err(eof_error())
+    }
+}
+
+/// Wraps a `PacketDataSource`, silently skipping any packet for which
+/// `predicate` returns `false`. A source EOF propagates unchanged.
+pub struct Filter<F> {
+    inner: Box<dyn PacketDataSource>,
+    predicate: F,
+}
+
+impl<F> Filter<F>
+where
+    F: Fn(&[u8], &CaptureInfo) -> bool,
+{
+    pub fn new(inner: Box<dyn PacketDataSource>, predicate: F) -> Self {
+        Filter { inner, predicate }
+    }
+}
+
+impl<F> PacketDataSource for Filter<F>
+where
+    F: Fn(&[u8], &CaptureInfo) -> bool,
+{
+    fn read_packet_data(&self) -> Result<(Vec<u8>, CaptureInfo), Box<dyn Error>> {
+        loop {
+            let (data, info) = self.inner.read_packet_data()?;
+            if (self.predicate)(&data, &info) {
+                return Ok((data, info));
             }
         }
-        Err(Box::new(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "EOF")))
     }
 }
 
+/// Wraps a `PacketDataSource`, reporting clean EOF once `max` packets have
+/// been yielded, even if the underlying source has more.
+pub struct Limit {
+    inner: Box<dyn PacketDataSource>,
+    max: usize,
+    read: Cell<usize>,
+}
+
+impl Limit {
+    pub fn new(inner: Box<dyn PacketDataSource>, max: usize) -> Self {
+        Limit {
+            inner,
+            max,
+            read: Cell::new(0),
+        }
+    }
+}
+
+impl PacketDataSource for Limit {
+    fn read_packet_data(&self) -> Result<(Vec<u8>, CaptureInfo), Box<dyn Error>> {
+        if self.read.get() >= self.max {
+            return Err(eof_error());
+        }
+        let data = self.inner.read_packet_data()?;
+        self.read.set(self.read.get() + 1);
+        Ok(data)
+    }
+}
+
+/// Wraps a `PacketDataSource`, forwarding every successfully read packet to
+/// `callback` (e.g. for logging) before returning it unchanged.
+pub struct Tee<F> {
+    inner: Box<dyn PacketDataSource>,
+    callback: RefCell<F>,
+}
+
+impl<F> Tee<F>
+where
+    F: FnMut(&[u8], &CaptureInfo),
+{
+    pub fn new(inner: Box<dyn PacketDataSource>, callback: F) -> Self {
+        Tee {
+            inner,
+            callback: RefCell::new(callback),
+        }
+    }
+}
+
+impl<F> PacketDataSource for Tee<F>
+where
+    F: FnMut(&[u8], &CaptureInfo),
+{
+    fn read_packet_data(&self) -> Result<(Vec<u8>, CaptureInfo), Box<dyn Error>> {
+        let (data, info) = self.inner.read_packet_data()?;
+        (self.callback.borrow_mut())(&data, &info);
+        Ok((data, info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct VecSource {
+        packets: RefCell<Vec<(Vec<u8>, CaptureInfo)>>,
+    }
+
+    impl VecSource {
+        fn new(packets: Vec<(Vec<u8>, CaptureInfo)>) -> Self {
+            VecSource {
+                packets: RefCell::new(packets),
+            }
+        }
+    }
+
+    impl PacketDataSource for VecSource {
+        fn read_packet_data(&self) -> Result<(Vec<u8>, CaptureInfo), Box<dyn Error>> {
+            let mut packets = self.packets.borrow_mut();
+            if packets.is_empty() {
+                return Err(eof_error());
+            }
+            Ok(packets.remove(0))
+        }
+    }
+
+    fn packet(byte: u8) -> (Vec<u8>, CaptureInfo) {
+        (
+            vec![byte],
+            CaptureInfo {
+                timestamp: std::time::UNIX_EPOCH,
+                capture_length: 1,
+                length: 1,
+                interface_index: 0,
+                ancillary_data: Vec::new(),
+            },
+        )
+    }
+
+    fn read_all(source: &dyn PacketDataSource) -> Vec<Vec<u8>> {
+        let mut out = Vec::new();
+        loop {
+            match source.read_packet_data() {
+                Ok((data, _)) => out.push(data),
+                Err(e) if is_eof(&*e) => break,
+                Err(e) => panic!("unexpected error: {e}"),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn concat_advances_to_the_next_source_on_eof() {
+        let first = Box::new(VecSource::new(vec![packet(1), packet(2)]));
+        let second = Box::new(VecSource::new(vec![packet(3)]));
+        let concat = Concat::new(vec![first, second]);
+
+        assert_eq!(read_all(&concat), vec![vec![1], vec![2], vec![3]]);
+    }
+
+    #[test]
+    fn filter_drops_packets_failing_the_predicate() {
+        let source = Box::new(VecSource::new(vec![packet(1), packet(2), packet(3)]));
+        let filter = Filter::new(source, |data, _| data[0] % 2 == 0);
+
+        assert_eq!(read_all(&filter), vec![vec![2]]);
+    }
+
+    #[test]
+    fn limit_reports_eof_after_max_packets() {
+        let source = Box::new(VecSource::new(vec![packet(1), packet(2), packet(3)]));
+        let limit = Limit::new(source, 2);
+
+        assert_eq!(read_all(&limit), vec![vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn tee_forwards_every_packet_to_the_callback() {
+        let source = Box::new(VecSource::new(vec![packet(1), packet(2)]));
+        let seen = RefCell::new(Vec::new());
+        let tee = Tee::new(source, |data: &[u8], _: &CaptureInfo| seen.borrow_mut().push(data.to_vec()));
+
+        assert_eq!(read_all(&tee), vec![vec![1], vec![2]]);
+        assert_eq!(*seen.borrow(), vec![vec![1], vec![2]]);
+    }
+}
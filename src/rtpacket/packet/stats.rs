@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use crate::rtpacket::layertype::LayerTypeID;
+
+/// Opt-in decode metrics accumulated while a packet is built, gated by
+/// [`crate::rtpacket::packet::decodeoptions::DecodeOptions::collect_stats`].
+///
+/// `EagerPacket` holds one of these (`None` unless `collect_stats` is set,
+/// so a caller who never asks for stats pays no tracking cost beyond the
+/// `Option` check) and updates it as layers are added and decode failures
+/// or panics are recovered, rather than requiring every `DecodeFunc` to
+/// instrument itself by hand.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    /// Total number of layers added to the packet, including any
+    /// `DecodeFailure` layer produced when decoding breaks down.
+    pub layers_decoded: usize,
+    /// Bytes consumed by each layer type that was decoded, keyed by
+    /// `LayerTypeID`.
+    pub bytes_per_layer_type: HashMap<LayerTypeID, usize>,
+    /// Number of `DecodeFailure` layers produced.
+    pub decode_failures: usize,
+    /// Number of decode panics recovered via `recover_decode_error`.
+    pub panics_recovered: usize,
+    /// Number of times a pooled buffer was reused from a `BytePool`, via
+    /// `BytePool::get_with_stats`. Stays `0` unless a caller threads this
+    /// `Stats` through its own pool usage — no decode path in this crate
+    /// does so yet, since `new_packet`'s `pool`-backed path is still a
+    /// `todo!()` stub.
+    pub pool_hits: usize,
+    /// Number of times a `BytePool` had nothing to reuse and allocated. See
+    /// `pool_hits` for why this stays `0` by default.
+    pub pool_misses: usize,
+}
+
+impl Stats {
+    /// Creates an empty set of counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record_layer(&mut self, layer_type: LayerTypeID, bytes: usize) {
+        self.layers_decoded += 1;
+        *self.bytes_per_layer_type.entry(layer_type).or_insert(0) += bytes;
+    }
+
+    pub(crate) fn record_decode_failure(&mut self) {
+        self.decode_failures += 1;
+    }
+
+    pub(crate) fn record_panic_recovered(&mut self) {
+        self.panics_recovered += 1;
+    }
+
+    pub(crate) fn record_pool_hit(&mut self) {
+        self.pool_hits += 1;
+    }
+
+    pub(crate) fn record_pool_miss(&mut self) {
+        self.pool_misses += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_layer_accumulates_count_and_bytes_per_type() {
+        let mut stats = Stats::new();
+
+        stats.record_layer(1, 10);
+        stats.record_layer(1, 5);
+        stats.record_layer(2, 3);
+
+        assert_eq!(stats.layers_decoded, 3);
+        assert_eq!(stats.bytes_per_layer_type.get(&1), Some(&15));
+        assert_eq!(stats.bytes_per_layer_type.get(&2), Some(&3));
+    }
+
+    #[test]
+    fn new_stats_start_at_zero() {
+        let stats = Stats::new();
+        assert_eq!(stats.layers_decoded, 0);
+        assert_eq!(stats.decode_failures, 0);
+        assert_eq!(stats.panics_recovered, 0);
+        assert_eq!(stats.pool_hits, 0);
+        assert_eq!(stats.pool_misses, 0);
+        assert!(stats.bytes_per_layer_type.is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use std::cell::RefCell;
+use std::io::{self, Read};
+use std::rc::Rc;
+use std::thread::sleep;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::rtpacket::decode::{DecodeFunc, PacketBuilder};
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
+use crate::rtpacket::error::ErrorDecodeable;
+use crate::rtpacket::packet::decodeoptions::DecodeOptions;
+use crate::rtpacket::packet::eagerpacket::EagerPacket;
+
+/// Number of bytes in the per-record header `Decoder` reads before each
+/// packet's captured bytes: a 4-byte little-endian captured length, a
+/// 4-byte little-endian original wire length, and an 8-byte little-endian
+/// Unix-epoch-seconds timestamp.
+const RECORD_HEADER_LEN: usize = 4 + 4 + 8;
+
+/// How long `read_exact_gently` sleeps between retries while waiting for
+/// more bytes to arrive on a `follow`ed reader.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Largest `capture_length` a record header is allowed to claim, rejected
+/// before it's used to size an allocation. Generous enough for any
+/// jumbo-frame capture, but well short of a corrupted/hostile header being
+/// able to force a multi-gigabyte (or outright failing) allocation.
+const MAX_CAPTURE_LENGTH: usize = 1 << 20;
+
+/// Reads framed packet records off of a blocking `io::Read` and decodes each
+/// one into a fully-decoded `EagerPacket`, the way a pcap file or a tailed
+/// capture socket would be consumed.
+///
+/// Each record is a fixed header (captured length, original wire length,
+/// timestamp) followed by exactly `captured length` bytes of packet data.
+/// When `follow` is `true`, reads that hit a clean EOF right at a record
+/// boundary block and retry instead of ending the stream, so a
+/// still-growing capture file or a live socket can be tailed instead of
+/// exhausted. See [`PacketCodec`](crate::rtpacket::packet::codec::PacketCodec)
+/// for the async equivalent of this over an `AsyncRead`.
+pub struct Decoder<R: Read> {
+    reader: R,
+    first_decoder: DecodeFunc,
+    decode_options: DecodeOptions,
+    follow: bool,
+}
+
+impl<R: Read> Decoder<R> {
+    /// Creates a `Decoder` that decodes each record read from `reader` with
+    /// `first_decoder`, carrying `decode_options` into every packet it
+    /// builds. When `follow` is `true`, a clean EOF at a record boundary
+    /// retries instead of ending the stream.
+    pub fn new(
+        reader: R,
+        first_decoder: DecodeFunc,
+        decode_options: DecodeOptions,
+        follow: bool,
+    ) -> Self {
+        Decoder {
+            reader,
+            first_decoder,
+            decode_options,
+            follow,
+        }
+    }
+
+    /// Reads and fully decodes the next packet record.
+    ///
+    /// Returns `Ok(None)` at a clean end of stream; this is only possible
+    /// when `follow` is `false` and the reader closes right before a new
+    /// record's header — anywhere else (mid-header, mid-body) a closed
+    /// reader is reported as an error instead of a clean end.
+    pub fn next_packet(&mut self) -> Result<Option<EagerPacket>, DecodeError> {
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        if !self.read_exact_gently(&mut header, true)? {
+            return Ok(None);
+        }
+
+        let capture_length = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let length = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+        let timestamp_secs = u64::from_le_bytes(header[8..16].try_into().unwrap());
+
+        if capture_length > MAX_CAPTURE_LENGTH {
+            return Err(DecodeError::new(
+                &format!(
+                    "record header claims a capture length of {capture_length} bytes, \
+                     which exceeds the {MAX_CAPTURE_LENGTH} byte limit"
+                ),
+                None,
+            ));
+        }
+
+        let mut body = vec![0u8; capture_length];
+        self.read_exact_gently(&mut body, false)?;
+
+        let data: Rc<[u8]> = Rc::from(body);
+        let mut packet = EagerPacket::new(data.clone(), self.decode_options);
+        packet.metadata.timestamp = UNIX_EPOCH
+            .checked_add(Duration::from_secs(timestamp_secs))
+            .ok_or_else(|| {
+                DecodeError::new(
+                    &format!("record header timestamp {timestamp_secs} overflows SystemTime"),
+                    None,
+                )
+            })?;
+        packet.metadata.capture_length = capture_length;
+        packet.metadata.length = length;
+
+        // Keep a concrete handle so we can reclaim the `EagerPacket` after
+        // decoding, while `first_decoder` itself only ever sees the `dyn
+        // PacketBuilder` trait object it's written against.
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+
+        (self.first_decoder)(data, builder)?;
+
+        Ok(Some(
+            Rc::try_unwrap(packet_handle)
+                .unwrap_or_else(|_| panic!("decoder retained a handle past its call"))
+                .into_inner(),
+        ))
+    }
+
+    /// Like `Read::read_exact`, but tolerant of a reader that has nothing to
+    /// offer right now rather than having genuinely reached its end.
+    ///
+    /// `allow_clean_eof` should be `true` only when `buf` is the very start
+    /// of a new record's header; everywhere else an `Ok(0)` read means the
+    /// reader closed partway through a record, which is always an error.
+    ///
+    /// When `self.follow` is `false`, an `Ok(0)` where `allow_clean_eof` is
+    /// `true` and nothing has been read yet ends the stream (`Ok(false)`).
+    /// When `self.follow` is `true`, every `Ok(0)` instead sleeps briefly
+    /// and retries, whether at a record boundary or mid-record, since the
+    /// reader is assumed to be a still-growing file or socket rather than
+    /// truly done.
+    fn read_exact_gently(&mut self, buf: &mut [u8], allow_clean_eof: bool) -> Result<bool, DecodeError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => {
+                    if self.follow {
+                        sleep(FOLLOW_POLL_INTERVAL);
+                        continue;
+                    }
+                    if allow_clean_eof && filled == 0 {
+                        return Ok(false);
+                    }
+                    return Err(DecodeError::new("reader closed mid-record", None));
+                }
+                Ok(n) => filled += n,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    return Err(DecodeError::new(
+                        "failed to read packet record",
+                        Some(Box::new(err)),
+                    ))
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = Result<EagerPacket, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_packet().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::rtpacket::decode::decodefragment::fragment_decoder;
+
+    fn record(capture_length: u32, length: u32, timestamp_secs: u64, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&capture_length.to_le_bytes());
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&timestamp_secs.to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn next_packet_decodes_a_single_record() {
+        let data = [1u8, 2, 3, 4];
+        let bytes = record(data.len() as u32, data.len() as u32, 1_704_164_645, &data);
+
+        let mut decoder = Decoder::new(Cursor::new(bytes), fragment_decoder(), DecodeOptions::default(), false);
+
+        let packet = decoder
+            .next_packet()
+            .expect("record should decode")
+            .expect("a packet should have been read");
+
+        assert_eq!(packet.data.as_ref(), &data);
+        assert_eq!(packet.metadata.capture_length, data.len());
+        assert_eq!(packet.metadata.length, data.len());
+        assert_eq!(
+            packet.metadata.timestamp,
+            UNIX_EPOCH + Duration::from_secs(1_704_164_645)
+        );
+        assert_eq!(packet.layers.len(), 1, "the fragment decoder should have added a layer");
+    }
+
+    #[test]
+    fn next_packet_rejects_a_header_claiming_an_oversized_capture_length() {
+        let mut header = Vec::new();
+        header.extend_from_slice(&(MAX_CAPTURE_LENGTH as u32 + 1).to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&0u64.to_le_bytes());
+
+        let mut decoder = Decoder::new(Cursor::new(header), fragment_decoder(), DecodeOptions::default(), false);
+
+        decoder
+            .next_packet()
+            .expect_err("a capture length over MAX_CAPTURE_LENGTH should be rejected before allocating");
+    }
+
+    #[test]
+    fn next_packet_returns_none_at_a_clean_record_boundary() {
+        let mut decoder = Decoder::new(Cursor::new(Vec::new()), fragment_decoder(), DecodeOptions::default(), false);
+
+        assert!(decoder.next_packet().expect("empty reader is a clean EOF").is_none());
+    }
+
+    #[test]
+    fn next_packet_errors_on_a_reader_closed_mid_record() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(&[1, 2]); // only 2 of the promised 4 body bytes
+
+        let mut decoder = Decoder::new(Cursor::new(bytes), fragment_decoder(), DecodeOptions::default(), false);
+
+        decoder
+            .next_packet()
+            .expect_err("a reader closing mid-record should not look like a clean EOF");
+    }
+
+    #[test]
+    fn iterator_yields_every_record_in_order() {
+        let mut bytes = record(3, 3, 0, &[1, 2, 3]);
+        bytes.extend(record(2, 2, 0, &[4, 5]));
+
+        let decoder = Decoder::new(Cursor::new(bytes), fragment_decoder(), DecodeOptions::default(), false);
+
+        let packets: Vec<Rc<[u8]>> = decoder
+            .map(|result| result.expect("both records should decode").data)
+            .collect();
+
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].as_ref(), &[1, 2, 3]);
+        assert_eq!(packets[1].as_ref(), &[4, 5]);
+    }
+}
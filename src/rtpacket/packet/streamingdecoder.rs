@@ -0,0 +1,250 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use bytes::{Buf, BytesMut};
+
+use crate::rtpacket::decode::{DecodeFunc, PacketBuilder};
+use crate::rtpacket::error::packetdecodeerror::DecodeError;
+use crate::rtpacket::error::{ErrorDecodeable, PacketError};
+use crate::rtpacket::packet::decodeoptions::DecodeOptions;
+use crate::rtpacket::packet::eagerpacket::EagerPacket;
+
+/// Number of bytes in the per-record header `StreamingDecoder` reads before
+/// each packet's captured bytes, matching
+/// [`crate::rtpacket::packet::streamdecoder::Decoder`]'s record framing: a
+/// 4-byte little-endian captured length, a 4-byte little-endian original
+/// wire length, and an 8-byte little-endian Unix-epoch-seconds timestamp.
+const RECORD_HEADER_LEN: usize = 4 + 4 + 8;
+
+/// Largest `capture_length` a record header is allowed to claim, mirroring
+/// the same guard in [`crate::rtpacket::packet::streamdecoder::Decoder`] and
+/// [`crate::rtpacket::packet::codec::PacketCodec`].
+const MAX_CAPTURE_LENGTH: usize = 1 << 20;
+
+/// Push-based counterpart to
+/// [`streamdecoder::Decoder`](crate::rtpacket::packet::streamdecoder::Decoder)
+/// for callers that can't hand over a blocking `io::Read` — an async socket
+/// polled a chunk at a time, or bytes arriving off of a channel — and need to
+/// feed bytes in as they arrive instead of blocking a thread on each read.
+///
+/// Records are framed the same way `streamdecoder::Decoder` reads them: a
+/// capture-length / wire-length / timestamp header followed by exactly that
+/// many bytes of packet data. `push` accumulates bytes into an internal
+/// buffer and only decodes once a full record is available; a record that's
+/// only partially buffered is left untouched, so a `push` that doesn't
+/// complete one never re-decodes a record that's already been handed back —
+/// the already-consumed bytes are drained from the buffer via `BytesMut`, and
+/// only the incomplete remainder is kept for the next call.
+///
+/// Every `DecodeFunc` in this crate still reports a malformed or
+/// too-short layer the way it always has, via `DecodeFeedback::set_truncated`
+/// plus a plain `DecodeError` — that signature isn't changed here. Since
+/// `decoder` is only ever invoked once a record's full declared
+/// `capture_length` is buffered, a decode failure at that point means the
+/// record's declared length doesn't actually fit its layers, which more
+/// bytes wouldn't fix, so it's surfaced as `PacketError::Decode` rather than
+/// `PacketError::NeedMoreData`.
+///
+/// # Limitations
+///
+/// Resumption here is at *record* granularity, not *mid-layer*: `push`
+/// suspends (internally, via `PacketError::NeedMoreData`, see `poll` below)
+/// before `decoder` is ever called, and resumes by re-trying the same call
+/// once `capture_length` more bytes have arrived; once `decoder` runs, it
+/// decodes the whole record in one pass. This isn't a shortcut this type
+/// takes — it's the only granularity the crate's layer representation can
+/// support without a breaking change. `Layer`/`Payloadable::decode_from_bytes`
+/// hand each layer an owned, already-sized `Rc<[u8]>` snapshot rather than a
+/// cursor into a growing buffer; `next_decoder` derives every later layer's
+/// input from the *previous* layer's stored `layer_payload()`, frozen at the
+/// moment that layer decoded. Buffering more bytes after a layer has already
+/// decoded can't retroactively grow that stored slice, so there's no way for
+/// a later `push` to hand an in-progress layer more of itself to work with —
+/// every registered `Layer` would need to hold a cursor into a shared,
+/// resizable buffer instead of an owned snapshot for that to be possible,
+/// which is a wider redesign than this type can make unilaterally.
+pub struct StreamingDecoder {
+    decoder: DecodeFunc,
+    options: DecodeOptions,
+    buffer: BytesMut,
+}
+
+impl StreamingDecoder {
+    /// Creates a `StreamingDecoder` that decodes each pushed record with
+    /// `decoder`, carrying `options` into every packet it builds.
+    pub fn new(decoder: DecodeFunc, options: DecodeOptions) -> Self {
+        StreamingDecoder {
+            decoder,
+            options,
+            buffer: BytesMut::new(),
+        }
+    }
+
+    /// Appends `chunk` to the internal reassembly buffer and decodes the
+    /// next record if the buffer now holds a complete one.
+    ///
+    /// Returns `Ok(None)` if a full record isn't yet available; `chunk`'s
+    /// bytes are kept, and nothing already buffered is discarded or
+    /// re-decoded. If more than one record's worth of bytes has been pushed,
+    /// only the first is decoded and returned — call `push` again with an
+    /// empty chunk to drain any further records already sitting in the
+    /// buffer.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Option<EagerPacket>, PacketError> {
+        self.buffer.extend_from_slice(chunk);
+        match self.poll() {
+            Ok(packet) => Ok(Some(packet)),
+            Err(PacketError::NeedMoreData) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Decodes the next record if one is fully buffered, or reports
+    /// `PacketError::NeedMoreData` if `push` needs to be called again first.
+    /// `push` itself turns that variant back into `Ok(None)` so its own
+    /// contract (an incomplete record isn't an error) doesn't change; this
+    /// is what actually constructs `NeedMoreData`, instead of leaving it
+    /// dead code.
+    fn poll(&mut self) -> Result<EagerPacket, PacketError> {
+        if self.buffer.len() < RECORD_HEADER_LEN {
+            return Err(PacketError::NeedMoreData);
+        }
+
+        let capture_length = u32::from_le_bytes(self.buffer[0..4].try_into().unwrap()) as usize;
+        let length = u32::from_le_bytes(self.buffer[4..8].try_into().unwrap()) as usize;
+        let timestamp_secs = u64::from_le_bytes(self.buffer[8..16].try_into().unwrap());
+
+        if capture_length > MAX_CAPTURE_LENGTH {
+            // Drop the offending header so a caller that keeps pushing isn't
+            // stuck re-seeing the same oversized claim forever.
+            self.buffer.advance(RECORD_HEADER_LEN);
+            return Err(PacketError::Decode(DecodeError::new(
+                &format!(
+                    "record header claims a capture length of {capture_length} bytes, \
+                     which exceeds the {MAX_CAPTURE_LENGTH} byte limit"
+                ),
+                None,
+            )));
+        }
+
+        if self.buffer.len() < RECORD_HEADER_LEN + capture_length {
+            self.buffer
+                .reserve(RECORD_HEADER_LEN + capture_length - self.buffer.len());
+            return Err(PacketError::NeedMoreData);
+        }
+
+        self.buffer.advance(RECORD_HEADER_LEN);
+        let data: Rc<[u8]> = Rc::from(self.buffer.split_to(capture_length).as_ref());
+
+        let mut packet = EagerPacket::new(data.clone(), self.options);
+        packet.metadata.timestamp = UNIX_EPOCH
+            .checked_add(Duration::from_secs(timestamp_secs))
+            .ok_or_else(|| {
+                PacketError::Decode(DecodeError::new(
+                    &format!("record header timestamp {timestamp_secs} overflows SystemTime"),
+                    None,
+                ))
+            })?;
+        packet.metadata.capture_length = capture_length;
+        packet.metadata.length = length;
+
+        // Keep a concrete handle so we can reclaim the `EagerPacket` after
+        // decoding, while `decoder` itself only ever sees the `dyn
+        // PacketBuilder` trait object it's written against.
+        let packet_handle = Rc::new(RefCell::new(packet));
+        let builder: Rc<RefCell<dyn PacketBuilder>> = packet_handle.clone();
+
+        match (self.decoder)(data, builder) {
+            Ok(()) => Ok(Rc::try_unwrap(packet_handle)
+                .unwrap_or_else(|_| panic!("decoder retained a handle past its call"))
+                .into_inner()),
+            Err(err) => Err(PacketError::Decode(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtpacket::decode::decodefragment::fragment_decoder;
+
+    fn record(capture_length: u32, length: u32, timestamp_secs: u64, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&capture_length.to_le_bytes());
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&timestamp_secs.to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn push_returns_none_until_the_record_is_complete() {
+        let mut decoder = StreamingDecoder::new(fragment_decoder(), DecodeOptions::streaming());
+        let bytes = record(4, 4, 1_704_164_645, &[1, 2, 3, 4]);
+
+        assert!(decoder
+            .push(&bytes[..RECORD_HEADER_LEN + 2])
+            .expect("a partial record is not an error")
+            .is_none());
+
+        let packet = decoder
+            .push(&bytes[RECORD_HEADER_LEN + 2..])
+            .expect("the rest of the record should decode")
+            .expect("the record is now complete");
+
+        assert_eq!(packet.data.as_ref(), &[1, 2, 3, 4]);
+        assert_eq!(packet.metadata.capture_length, 4);
+        assert_eq!(packet.metadata.length, 4);
+        assert_eq!(
+            packet.metadata.timestamp,
+            UNIX_EPOCH + Duration::from_secs(1_704_164_645)
+        );
+        assert_eq!(packet.layers.len(), 1, "the fragment decoder should have added a layer");
+    }
+
+    #[test]
+    fn push_decodes_a_record_delivered_in_a_single_chunk() {
+        let mut decoder = StreamingDecoder::new(fragment_decoder(), DecodeOptions::streaming());
+        let bytes = record(3, 3, 0, &[9, 8, 7]);
+
+        let packet = decoder
+            .push(&bytes)
+            .expect("a whole record should decode")
+            .expect("a complete record was pushed");
+
+        assert_eq!(packet.data.as_ref(), &[9, 8, 7]);
+    }
+
+    #[test]
+    fn push_leaves_a_completed_record_unrepeated_across_calls() {
+        let mut decoder = StreamingDecoder::new(fragment_decoder(), DecodeOptions::streaming());
+        let mut bytes = record(2, 2, 0, &[1, 2]);
+        bytes.extend(record(2, 2, 0, &[3, 4]));
+
+        let first = decoder
+            .push(&bytes)
+            .expect("the first record should decode")
+            .expect("a complete record was pushed");
+        assert_eq!(first.data.as_ref(), &[1, 2]);
+
+        // Draining the second record shouldn't require (or re-return) the first.
+        let second = decoder
+            .push(&[])
+            .expect("the already-buffered second record should decode")
+            .expect("a second record was already fully buffered");
+        assert_eq!(second.data.as_ref(), &[3, 4]);
+    }
+
+    #[test]
+    fn push_rejects_a_header_claiming_an_oversized_capture_length() {
+        let mut decoder = StreamingDecoder::new(fragment_decoder(), DecodeOptions::streaming());
+        let mut header = Vec::new();
+        header.extend_from_slice(&(MAX_CAPTURE_LENGTH as u32 + 1).to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&0u64.to_le_bytes());
+
+        decoder
+            .push(&header)
+            .expect_err("a capture length over MAX_CAPTURE_LENGTH should be rejected");
+    }
+}
@@ -0,0 +1,262 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::rtpacket::writer::{SerializeBuffer, SerializeableBuffer};
+
+/// Largest value [`SerializeBuffer::write_compact`] stores in one byte
+/// (mode `00`): six bits of value, the low two bits reserved for the mode.
+const SINGLE_BYTE_MAX: u64 = 0x3F;
+/// Largest value stored in two little-endian bytes (mode `01`): fourteen
+/// bits of value.
+const TWO_BYTE_MAX: u64 = 0x3FFF;
+/// Largest value stored in four little-endian bytes (mode `10`): thirty
+/// bits of value.
+const FOUR_BYTE_MAX: u64 = 0x3FFF_FFFF;
+
+impl SerializeBuffer {
+    /// Appends `value` to the buffer using the SCALE compact integer
+    /// encoding, choosing the smallest of its four modes that fits:
+    ///
+    /// * `00` - a single byte, `value << 2`, for `value <= 0x3F`.
+    /// * `01` - two little-endian bytes, `(value << 2) | 0b01`, for
+    ///   `value <= 0x3FFF`.
+    /// * `10` - four little-endian bytes, `(value << 2) | 0b10`, for
+    ///   `value <= 0x3FFF_FFFF`.
+    /// * `11` - "big-integer" mode: one byte whose upper six bits hold
+    ///   `bytes_needed - 4`, followed by `value` as little-endian bytes.
+    pub fn write_compact(&mut self, value: u64) -> Result<(), Box<dyn Error>> {
+        if value <= SINGLE_BYTE_MAX {
+            let dest = self.append_bytes(1)?;
+            dest[0] = (value as u8) << 2;
+        } else if value <= TWO_BYTE_MAX {
+            let encoded = ((value as u16) << 2) | 0b01;
+            let dest = self.append_bytes(2)?;
+            dest.copy_from_slice(&encoded.to_le_bytes());
+        } else if value <= FOUR_BYTE_MAX {
+            let encoded = ((value as u32) << 2) | 0b10;
+            let dest = self.append_bytes(4)?;
+            dest.copy_from_slice(&encoded.to_le_bytes());
+        } else {
+            let bytes_needed = bytes_needed_for(value);
+            let first_byte = (((bytes_needed - 4) as u8) << 2) | 0b11;
+            let dest = self.append_bytes(1 + bytes_needed)?;
+            dest[0] = first_byte;
+            dest[1..].copy_from_slice(&value.to_le_bytes()[..bytes_needed]);
+        }
+        Ok(())
+    }
+}
+
+/// The smallest number of little-endian bytes that hold `value` with no
+/// leading zero byte, used by the big-integer mode.
+fn bytes_needed_for(value: u64) -> usize {
+    let bits = 64 - value.leading_zeros() as usize;
+    bits.div_ceil(8)
+}
+
+/// Reads a SCALE compact integer from the start of `data`.
+///
+/// # Returns
+///
+/// The decoded value and the number of bytes consumed from `data`.
+///
+/// # Errors
+///
+/// Returns [`CompactError::UnexpectedEnd`] if `data` is too short for the
+/// mode its first byte selects, [`CompactError::TooLong`] if the
+/// big-integer mode's declared byte count would overflow a `u64`, or
+/// [`CompactError::NonCanonical`] if the value was encoded in a mode wider
+/// than the smallest one that fits it.
+pub fn read_compact(data: &[u8]) -> Result<(u64, usize), Box<dyn Error>> {
+    let first = *data.first().ok_or(CompactError::UnexpectedEnd)?;
+
+    match first & 0b11 {
+        0b00 => Ok(((first >> 2) as u64, 1)),
+        0b01 => {
+            if data.len() < 2 {
+                return Err(Box::new(CompactError::UnexpectedEnd));
+            }
+            let raw = u16::from_le_bytes([data[0], data[1]]);
+            let value = (raw >> 2) as u64;
+            if value <= SINGLE_BYTE_MAX {
+                return Err(Box::new(CompactError::NonCanonical));
+            }
+            Ok((value, 2))
+        }
+        0b10 => {
+            if data.len() < 4 {
+                return Err(Box::new(CompactError::UnexpectedEnd));
+            }
+            let raw = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            let value = (raw >> 2) as u64;
+            if value <= TWO_BYTE_MAX {
+                return Err(Box::new(CompactError::NonCanonical));
+            }
+            Ok((value, 4))
+        }
+        _ => {
+            let bytes_needed = (first >> 2) as usize + 4;
+            if bytes_needed > 8 {
+                return Err(Box::new(CompactError::TooLong));
+            }
+            if data.len() < 1 + bytes_needed {
+                return Err(Box::new(CompactError::UnexpectedEnd));
+            }
+
+            let mut buf = [0u8; 8];
+            buf[..bytes_needed].copy_from_slice(&data[1..1 + bytes_needed]);
+            let value = u64::from_le_bytes(buf);
+
+            if buf[bytes_needed - 1] == 0 || value <= FOUR_BYTE_MAX {
+                return Err(Box::new(CompactError::NonCanonical));
+            }
+            Ok((value, 1 + bytes_needed))
+        }
+    }
+}
+
+/// Errors returned by [`read_compact`].
+#[derive(Debug)]
+pub enum CompactError {
+    /// `data` ended before the bytes its first byte's mode requires.
+    UnexpectedEnd,
+    /// The big-integer mode declared more bytes than a `u64` can hold.
+    TooLong,
+    /// The value was encoded in a wider mode than the smallest one that fits
+    /// it, so this isn't the unique canonical encoding for that value.
+    NonCanonical,
+}
+
+impl fmt::Display for CompactError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompactError::UnexpectedEnd => write!(f, "compact integer runs past the end of the buffer"),
+            CompactError::TooLong => {
+                write!(f, "compact integer's big-integer mode exceeds 8 bytes")
+            }
+            CompactError::NonCanonical => {
+                write!(f, "compact integer is not encoded in its canonical (smallest) mode")
+            }
+        }
+    }
+}
+
+impl Error for CompactError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_byte_mode_covers_its_range() {
+        for value in [0u64, 1, 62, 63] {
+            let mut buffer = SerializeBuffer::new();
+            buffer.write_compact(value).unwrap();
+            assert_eq!(buffer.bytes().len(), 1);
+            assert_eq!(buffer.bytes()[0] & 0b11, 0b00);
+
+            let (decoded, len) = read_compact(buffer.bytes()).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(len, 1);
+        }
+    }
+
+    #[test]
+    fn two_byte_mode_covers_its_range() {
+        for value in [64u64, 300, 16383] {
+            let mut buffer = SerializeBuffer::new();
+            buffer.write_compact(value).unwrap();
+            assert_eq!(buffer.bytes().len(), 2);
+            assert_eq!(buffer.bytes()[0] & 0b11, 0b01);
+
+            let (decoded, len) = read_compact(buffer.bytes()).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(len, 2);
+        }
+    }
+
+    #[test]
+    fn four_byte_mode_covers_its_range() {
+        for value in [16384u64, 1_000_000, 0x3FFF_FFFF] {
+            let mut buffer = SerializeBuffer::new();
+            buffer.write_compact(value).unwrap();
+            assert_eq!(buffer.bytes().len(), 4);
+            assert_eq!(buffer.bytes()[0] & 0b11, 0b10);
+
+            let (decoded, len) = read_compact(buffer.bytes()).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(len, 4);
+        }
+    }
+
+    #[test]
+    fn big_integer_mode_covers_larger_values() {
+        for value in [0x4000_0000u64, u32::MAX as u64, u64::MAX, 1u64 << 40] {
+            let mut buffer = SerializeBuffer::new();
+            buffer.write_compact(value).unwrap();
+            assert_eq!(buffer.bytes()[0] & 0b11, 0b11);
+
+            let (decoded, len) = read_compact(buffer.bytes()).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(len, buffer.bytes().len());
+        }
+    }
+
+    #[test]
+    fn u64_max_takes_nine_bytes() {
+        let mut buffer = SerializeBuffer::new();
+        buffer.write_compact(u64::MAX).unwrap();
+        assert_eq!(buffer.bytes().len(), 9);
+    }
+
+    #[test]
+    fn rejects_non_canonical_two_byte_encoding_of_a_single_byte_value() {
+        // 10 (= value 10, mode 01) fits in a single byte, so two-byte mode
+        // is not canonical for it.
+        let encoded = ((10u16) << 2) | 0b01;
+        let result = read_compact(&encoded.to_le_bytes());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_non_canonical_four_byte_encoding_of_a_two_byte_value() {
+        let encoded = ((100u32) << 2) | 0b10;
+        let result = read_compact(&encoded.to_le_bytes());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_non_canonical_big_integer_encoding_of_a_four_byte_value() {
+        // n = 4 following bytes, value 100 fits in four-byte mode already.
+        let mut bytes = vec![0b11u8];
+        bytes.extend_from_slice(&100u32.to_le_bytes());
+        let result = read_compact(&bytes);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_big_integer_encoding_with_a_padded_leading_zero_byte() {
+        // n = 5 following bytes, but the 5th (most significant) byte is 0,
+        // so this should have been encoded with n = 4 instead.
+        let mut bytes = vec![0b00_0111u8]; // (5 - 4) << 2 | 0b11
+        bytes.extend_from_slice(&[1, 0, 0, 0, 0]);
+        let result = read_compact(&bytes);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert!(read_compact(&[]).is_err());
+        assert!(read_compact(&[0b01]).is_err());
+        assert!(read_compact(&[0b10]).is_err());
+        assert!(read_compact(&[0b11]).is_err());
+    }
+
+    #[test]
+    fn rejects_big_integer_length_beyond_eight_bytes() {
+        // (63 << 2) | 0b11 declares 67 following bytes.
+        let first = (63u8 << 2) | 0b11;
+        let result = read_compact(&[first]);
+        assert!(result.is_err());
+    }
+}
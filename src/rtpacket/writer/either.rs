@@ -0,0 +1,85 @@
+use std::error::Error;
+
+use crate::rtpacket::layertype::LayerType;
+use crate::rtpacket::writer::SerializeableBuffer;
+
+/// A buffer that is either of two concrete `SerializeableBuffer` implementations.
+///
+/// `Either` lets a function choose between, say, a freshly allocated
+/// `SerializeBuffer` and a caller-supplied fixed-capacity buffer while still
+/// returning a single concrete type. This avoids the dynamic dispatch and
+/// heap allocation of `Box<dyn SerializeableBuffer>`, since each method call
+/// matches on the variant once and calls straight through to the underlying
+/// implementation.
+pub enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+impl<A, B> Either<A, B> {
+    /// Applies `f` to the contents if this is the `A` variant, leaving a `B`
+    /// variant untouched.
+    pub fn map_a<C>(self, f: impl FnOnce(A) -> C) -> Either<C, B> {
+        match self {
+            Either::A(a) => Either::A(f(a)),
+            Either::B(b) => Either::B(b),
+        }
+    }
+
+    /// Applies `f` to the contents if this is the `B` variant, leaving an `A`
+    /// variant untouched.
+    pub fn map_b<C>(self, f: impl FnOnce(B) -> C) -> Either<A, C> {
+        match self {
+            Either::A(a) => Either::A(a),
+            Either::B(b) => Either::B(f(b)),
+        }
+    }
+}
+
+impl<A, B> SerializeableBuffer for Either<A, B>
+where
+    A: SerializeableBuffer,
+    B: SerializeableBuffer,
+{
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Either::A(a) => a.bytes(),
+            Either::B(b) => b.bytes(),
+        }
+    }
+
+    fn prepend_bytes(&mut self, num: usize) -> Result<&mut [u8], Box<dyn Error>> {
+        match self {
+            Either::A(a) => a.prepend_bytes(num),
+            Either::B(b) => b.prepend_bytes(num),
+        }
+    }
+
+    fn append_bytes(&mut self, num: usize) -> Result<&mut [u8], Box<dyn Error>> {
+        match self {
+            Either::A(a) => a.append_bytes(num),
+            Either::B(b) => b.append_bytes(num),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            Either::A(a) => a.clear(),
+            Either::B(b) => b.clear(),
+        }
+    }
+
+    fn layers(&self) -> Vec<LayerType> {
+        match self {
+            Either::A(a) => a.layers(),
+            Either::B(b) => b.layers(),
+        }
+    }
+
+    fn push_layer(&mut self, layer_type: LayerType) {
+        match self {
+            Either::A(a) => a.push_layer(layer_type),
+            Either::B(b) => b.push_layer(layer_type),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::error::Error;
+
+use crate::rtpacket::layertype::LayerType;
+use crate::rtpacket::writer::SerializeableBuffer;
+
+/// A `SerializeableBuffer` backed by an ordered list of byte fragments
+/// instead of one contiguous allocation.
+///
+/// Where `SerializeBuffer` reallocates and copies its entire body every time
+/// a `prepend_bytes` call outgrows its prefix capacity, `FragmentedSerializeBuffer`
+/// simply pushes a new fragment onto the front (or back) of the list. This is
+/// cheap for large, shared payloads: a layer prepending its header never
+/// touches the payload bytes that came before it.
+///
+/// Callers that need a flat buffer (e.g. handing data to a socket without
+/// scatter-gather `writev` support) can call `to_contiguous`. The
+/// `SerializeableBuffer::bytes` implementation keeps a contiguous copy
+/// up to date on every mutation, so it always returns a valid flat view; this
+/// trades away some of the zero-copy benefit for callers that only use the
+/// trait API, while `fragments()` remains zero-copy for callers that walk the
+/// fragment list directly.
+#[derive(Clone, Default)]
+pub struct FragmentedSerializeBuffer {
+    fragments: Vec<Box<[u8]>>,
+    layers: Vec<LayerType>,
+    contiguous: Vec<u8>,
+}
+
+impl FragmentedSerializeBuffer {
+    /// Creates a new, empty `FragmentedSerializeBuffer`.
+    pub fn new() -> Self {
+        FragmentedSerializeBuffer {
+            fragments: Vec::new(),
+            layers: Vec::new(),
+            contiguous: Vec::new(),
+        }
+    }
+
+    /// Returns the fragments that make up the buffer's body, in order.
+    ///
+    /// This is the zero-copy accessor: it returns references into the
+    /// existing fragments rather than materializing a flat buffer.
+    pub fn fragments(&self) -> Vec<&[u8]> {
+        self.fragments.iter().map(|f| f.as_ref()).collect()
+    }
+
+    /// Flattens all fragments into a single owned `Vec<u8>`.
+    pub fn to_contiguous(&self) -> Vec<u8> {
+        let total: usize = self.fragments.iter().map(|f| f.len()).sum();
+        let mut out = Vec::with_capacity(total);
+        for fragment in &self.fragments {
+            out.extend_from_slice(fragment);
+        }
+        out
+    }
+
+    /// Returns the total length of the body, summed across all fragments,
+    /// without materializing a contiguous copy. Layers whose header encodes
+    /// a total-length field (`fix_lengths`) can use this instead of
+    /// `to_contiguous().len()`.
+    pub fn body_len(&self) -> usize {
+        self.fragments.iter().map(|f| f.len()).sum()
+    }
+
+    fn rebuild_contiguous(&mut self) {
+        self.contiguous = self.to_contiguous();
+    }
+}
+
+impl SerializeableBuffer for FragmentedSerializeBuffer {
+    fn bytes(&self) -> &[u8] {
+        &self.contiguous
+    }
+
+    fn prepend_bytes(&mut self, num: usize) -> Result<&mut [u8], Box<dyn Error>> {
+        self.fragments.insert(0, vec![0u8; num].into_boxed_slice());
+        self.rebuild_contiguous();
+        Ok(&mut self.fragments[0])
+    }
+
+    fn append_bytes(&mut self, num: usize) -> Result<&mut [u8], Box<dyn Error>> {
+        self.fragments.push(vec![0u8; num].into_boxed_slice());
+        self.rebuild_contiguous();
+        let last = self.fragments.len() - 1;
+        Ok(&mut self.fragments[last])
+    }
+
+    fn clear(&mut self) {
+        self.fragments.clear();
+        self.layers.clear();
+        self.contiguous.clear();
+    }
+
+    fn layers(&self) -> Vec<LayerType> {
+        self.layers.clone()
+    }
+
+    fn push_layer(&mut self, layer_type: LayerType) {
+        self.layers.push(layer_type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepend_and_append_preserve_order() {
+        let mut buffer = FragmentedSerializeBuffer::new();
+
+        buffer.append_bytes(2).unwrap().copy_from_slice(&[3, 4]);
+        buffer.prepend_bytes(2).unwrap().copy_from_slice(&[1, 2]);
+        buffer.append_bytes(2).unwrap().copy_from_slice(&[5, 6]);
+
+        assert_eq!(buffer.to_contiguous(), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(buffer.bytes(), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(buffer.body_len(), 6);
+    }
+
+    #[test]
+    fn clear_resets_fragments_and_layers() {
+        let mut buffer = FragmentedSerializeBuffer::new();
+        buffer.append_bytes(4).unwrap();
+
+        buffer.clear();
+
+        assert!(buffer.fragments().is_empty());
+        assert_eq!(buffer.body_len(), 0);
+        assert!(buffer.bytes().is_empty());
+    }
+}
@@ -1,8 +1,18 @@
 use std::error::Error;
 use std::fmt;
+use std::rc::Rc;
 
 use crate::rtpacket::layertype::LayerType;
 
+pub mod compact;
+pub mod either;
+pub mod fragmented;
+pub mod varint;
+pub use compact::{read_compact, CompactError};
+pub use either::Either;
+pub use fragmented::FragmentedSerializeBuffer;
+pub use varint::{read_signed_varint, read_varint, zigzag_decode, zigzag_encode, VarintError};
+
 /// A trait for types that can be serialized into a byte representation.
 ///
 /// This trait allows its implementations to be written out as a set of bytes,
@@ -32,6 +42,14 @@ pub trait SerializableLayer {
     /// failure. If an error is returned, the contents of `buffer` should be
     /// considered invalidated and not used.
     ///
+    /// # Errors
+    ///
+    /// When `options.fix_lengths` is set, implementations that recompute a
+    /// length field from `buffer.bytes().len()` must check that the length
+    /// fits in that field's width before casting (via [`fits_in_u16`] /
+    /// [`fits_in_u32`]) and return [`SerializeError::length_overflow`]
+    /// rather than truncating the value into an undersized header field.
+    ///
     /// # Note
     ///
     /// Implementations should entirely ignore `LayerContents` and `LayerPayload`,
@@ -52,6 +70,48 @@ pub trait SerializableLayer {
     ///
     /// A `LayerType` indicating the type of the layer.
     fn layer_type(&self) -> LayerType;
+
+    /// Returns the size constraints this layer imposes on the body it wraps.
+    ///
+    /// [`serialize_nested`] uses this to validate body sizes ahead of time
+    /// and to size a single `SerializeBuffer` for the whole packet up front,
+    /// without the reallocations `serialize_layers` incurs as each outer
+    /// header grows the buffer's front. Layers with no framing of their own
+    /// and no restriction on the body they wrap (e.g.
+    /// [`Payload`](crate::rtpacket::base::payload::Payload) or
+    /// [`Fragment`](crate::rtpacket::base::fragment::Fragment)) can rely on
+    /// the default.
+    fn constraints(&self) -> LayerConstraints {
+        LayerConstraints::default()
+    }
+}
+
+/// Size and validity constraints a [`SerializableLayer`] imposes on the body
+/// it wraps. See [`SerializableLayer::constraints`].
+#[derive(Debug, Clone, Copy)]
+pub struct LayerConstraints {
+    /// Bytes this layer prepends in front of its body, i.e. its header.
+    pub header_len: usize,
+    /// Bytes this layer appends after its body, e.g. a trailer or FCS.
+    pub footer_len: usize,
+    /// The smallest body, in bytes, this layer is willing to wrap.
+    pub min_body_len: usize,
+    /// The largest body, in bytes, this layer is willing to wrap.
+    pub max_body_len: usize,
+}
+
+impl Default for LayerConstraints {
+    /// No header or footer, and no restriction on the body's size — the
+    /// correct default for layers that neither add framing nor constrain
+    /// what they wrap.
+    fn default() -> Self {
+        LayerConstraints {
+            header_len: 0,
+            footer_len: 0,
+            min_body_len: 0,
+            max_body_len: usize::MAX,
+        }
+    }
 }
 
 /// Options for controlling serialization behavior of `SerializableLayer` implementations.
@@ -72,10 +132,14 @@ pub struct SerializeOptions {
     /// Determines whether checksums should be recalculated based on the layer's
     /// payload during serialization.
     ///
-    /// Setting this to `true` triggers a recomputation of checksums for layers
-    /// that include such validation mechanisms. This ensures that serialized
-    /// data maintains integrity and conforms to protocol specifications that
-    /// require checksum validation.
+    /// When set to `true`, a [`SerializableLayer::serialize_to`] implementation
+    /// whose layer carries a checksum (e.g. IPv4's header checksum, or a
+    /// TCP/UDP transport checksum) should: zero its checksum field, compute
+    /// the real value via [`crate::rtpacket::checksum::compute_layer_checksum`]
+    /// over the freshly written header and `buffer.bytes()` payload, then
+    /// patch the field in place. This ensures that serialized data maintains
+    /// integrity and conforms to protocol specifications that require
+    /// checksum validation.
     pub compute_checksums: bool,
 }
 
@@ -140,17 +204,26 @@ pub trait SerializeableBuffer {
     fn push_layer(&mut self, layer_type: LayerType);
 }
 
+/// A growable byte buffer split into three regions: a front prefix (unused
+/// capacity reserved for future `prepend_bytes` calls), the live body
+/// (`body_start..body_start + body_len`), and a rear suffix (unused capacity
+/// reserved for future `append_bytes` calls). `data.len()` always equals
+/// `body_start + body_len` — the suffix exists only as spare `Vec` capacity,
+/// never as materialized length.
 #[derive(Clone)]
 pub struct SerializeBuffer {
     data: Vec<u8>,
-    // Replaces []byte
-    start: usize,
-    // Equivalent to 'int' but more precise in Rust; usize is commonly used for indexing
-    prepended: usize,
-    // Replaces 'prepended int'
-    appended: usize,
-    // Replaces 'appended int'
-    layers: Vec<LayerType>, // Assuming LayerType is already defined somewhere
+    /// Index into `data` where the live body begins.
+    body_start: usize,
+    /// Length of the live body.
+    body_len: usize,
+    /// Cumulative amount the prefix region has ever grown by. `Vec` doubles
+    /// its own capacity automatically when growing from the back, but it has
+    /// no equivalent notion of growing from the front, so `prepend_bytes`
+    /// tracks this itself and grows the prefix by at least as much as last
+    /// time, reproducing the same exponential-doubling behavior by hand.
+    prefix_growth: usize,
+    layers: Vec<LayerType>,
 }
 
 impl SerializeBuffer {
@@ -160,10 +233,10 @@ impl SerializeBuffer {
     /// of operations is not known in advance.
     pub(crate) fn new() -> Self {
         SerializeBuffer {
-            data: Vec::new(), // No preallocated space, will grow as needed.
-            start: 0,         // Since there's no preallocation, start is 0.
-            prepended: 0,
-            appended: 0,
+            data: Vec::new(),
+            body_start: 0,
+            body_len: 0,
+            prefix_growth: 0,
             layers: vec![],
         }
     }
@@ -175,32 +248,80 @@ impl SerializeBuffer {
     ///
     /// * `expected_prepend_length` - The expected number of bytes to prepend.
     /// * `expected_append_length` - The expected number of bytes to append.
-    fn new_default(expected_prepend_length: usize, expected_append_length: usize) -> Self {
-        // Preallocate buffer size based on expected prepend and append lengths.
+    pub fn new_default(expected_prepend_length: usize, expected_append_length: usize) -> Self {
+        // Preallocate room for both regions, but only materialize the prefix
+        // into `data`'s length; the append room lives purely in its spare
+        // capacity, leaving the (empty) body sitting in the middle with
+        // room to grow in either direction.
         let capacity = expected_prepend_length + expected_append_length;
-        let mut buffer = Vec::with_capacity(capacity);
-
-        // Initialize the buffer with zeros for the expected prepend length to simulate
-        // the space where data will be prepended. This ensures that the prepend operation
-        // can be done efficiently.
-        buffer.resize(expected_prepend_length, 0u8);
+        let mut data = Vec::with_capacity(capacity);
+        data.resize(expected_prepend_length, 0u8);
 
         SerializeBuffer {
-            data: buffer,
-            start: expected_prepend_length,
-            prepended: 0,
-            appended: 0,
+            data,
+            body_start: expected_prepend_length,
+            body_len: 0,
+            prefix_growth: expected_prepend_length,
             layers: vec![],
         }
     }
+
+    /// Grows the prefix region, if necessary, so that at least `num` bytes
+    /// are free in front of the body. Shared by `prepend_bytes` and
+    /// `reserve_front`.
+    fn grow_prefix(&mut self, num: usize) {
+        let to_prepend = std::cmp::max(self.prefix_growth, num);
+        self.prefix_growth += to_prepend;
+
+        let new_len = self.data.len() + to_prepend;
+        let mut new_data = vec![0u8; new_len];
+        let new_body_start = self.body_start + to_prepend;
+        new_data[new_body_start..new_body_start + self.body_len]
+            .copy_from_slice(&self.data[self.body_start..self.body_start + self.body_len]);
+
+        self.body_start = new_body_start;
+        self.data = new_data;
+    }
+
+    /// Pre-grows the prefix region so a subsequent `prepend_bytes(num)` is
+    /// guaranteed not to reallocate. Useful when the total header size of a
+    /// serialization is known up front.
+    pub fn reserve_front(&mut self, num: usize) {
+        if self.body_start < num {
+            self.grow_prefix(num);
+        }
+    }
+
+    /// Pre-grows the suffix region so a subsequent `append_bytes(num)` is
+    /// guaranteed not to reallocate. Useful when the total trailer size of a
+    /// serialization is known up front.
+    pub fn reserve_back(&mut self, num: usize) {
+        self.data.reserve(num);
+    }
+
+    /// Rewinds the buffer to an empty body, ready to serialize another
+    /// packet, without dropping the backing `Vec`.
+    ///
+    /// Unlike `clear`, which copies the surviving prefix into a freshly
+    /// allocated `Vec` (shrinking its capacity to fit), `reset_for_reuse`
+    /// truncates the existing allocation in place. A caller that serializes
+    /// packet after packet into the same buffer via `new_default` followed by
+    /// repeated `reset_for_reuse` calls amortizes allocation to zero once the
+    /// buffer has grown to accommodate the largest packet seen so far.
+    pub fn reset_for_reuse(&mut self) {
+        self.body_start = self.prefix_growth;
+        self.data.truncate(self.body_start);
+        self.body_len = 0;
+        self.layers.clear();
+    }
 }
 
-impl<'a> SerializeableBuffer for SerializeBuffer {
-    /// Returns a slice to the bytes in the buffer that contains any data written.
-    /// This slice starts from the `start` position, effectively skipping any preallocated
-    /// space meant for prepending data.
+impl SerializeableBuffer for SerializeBuffer {
+    /// Returns a slice to the live body of the buffer, excluding any free
+    /// prefix or suffix capacity reserved for future `prepend_bytes`/
+    /// `append_bytes` calls.
     fn bytes(&self) -> &[u8] {
-        &self.data[self.start..]
+        &self.data[self.body_start..self.body_start + self.body_len]
     }
 
     /// Prepends the specified number of bytes to the start of the buffer.
@@ -216,43 +337,26 @@ impl<'a> SerializeableBuffer for SerializeBuffer {
     ///
     /// Returns an error if the operation cannot be completed, e.g., due to allocation failure.
     fn prepend_bytes(&mut self, num: usize) -> Result<&mut [u8], Box<dyn Error>> {
-        if self.start < num {
-            // The number of bytes to prepend.
-            let mut to_prepend = self.prepended;
-            // If the number of bytes to prepend is less than the specified number, set it to the specified number.
-            if to_prepend < num {
-                to_prepend = num;
-            }
-            // Increment the number of prepended bytes.
-            self.prepended += to_prepend;
-            // Calculate the new length of the data vector, including the number of prepended bytes.
-            let length = self.data.capacity() + to_prepend;
-            // Create a new vector with the calculated length and fill it with zeros.
-            let mut new_data = vec![0u8; length];
-            // Calculate the new start position, including the number of prepended bytes.
-            let new_start = self.start + to_prepend;
-            // Copy the existing data into the new vector, starting from the current start position.
-            new_data[new_start..].copy_from_slice(&self.data[self.start..]);
-            // Update the start position to include the number of prepended bytes.
-            self.start += new_start;
-            // Update the data vector to include the prepended bytes.
-            self.data = new_data[..to_prepend + self.data.len()].to_owned();
+        if self.body_start < num {
+            self.grow_prefix(num);
         }
-        // Set the start position to the current position minus the number of prepended bytes.
-        self.start -= num;
-        // Return a mutable slice to the prepended bytes.
-        Ok(&mut self.data[self.start..self.start + num])
+        self.body_start -= num;
+        self.body_len += num;
+        Ok(&mut self.data[self.body_start..self.body_start + num])
     }
 
+    /// Appends the specified number of bytes to the end of the buffer.
+    ///
+    /// Unlike `prepend_bytes`, this leans on `Vec::reserve`'s own amortized
+    /// growth instead of hand-rolling a doubling scheme: the suffix region is
+    /// just `data`'s spare capacity, so growing it is a matter of asking the
+    /// `Vec` for more room before extending its length into that room.
     fn append_bytes(&mut self, num: usize) -> Result<&mut [u8], Box<dyn Error>> {
-        let initial_length = self.data.len();
-        if self.data.capacity() - initial_length < num {
-            let to_append = std::cmp::max(self.appended, num);
-            self.appended += to_append;
-            self.data.reserve(to_append);
-        }
-        self.data.resize(initial_length + num, 0u8);
-        Ok(&mut self.data[initial_length..])
+        let body_end = self.body_start + self.body_len;
+        self.data.reserve(num);
+        self.data.resize(body_end + num, 0u8);
+        self.body_len += num;
+        Ok(&mut self.data[body_end..body_end + num])
     }
 
     /// Clears the given write buffer, then serializes and writes all provided layers into it
@@ -271,8 +375,9 @@ impl<'a> SerializeableBuffer for SerializeBuffer {
     /// This function returns a `Result<(), Box<dyn Error>>`. On success, it returns `Ok(())`.
     /// On failure, it returns an `Err` with the error that occurred during serialization.
     fn clear(&mut self) {
-        self.start = self.prepended;
-        self.data = self.data[..self.start].to_owned();
+        self.body_start = self.prefix_growth;
+        self.data = vec![0u8; self.body_start];
+        self.body_len = 0;
         self.layers = vec![];
     }
 
@@ -289,6 +394,11 @@ impl<'a> SerializeableBuffer for SerializeBuffer {
 /// such that they correctly wrap each other. It's important to note that by clearing
 /// the buffer, it invalidates all slices previously returned by the buffer's `bytes` method.
 ///
+/// `layers` accepts either `Box<dyn SerializableLayer>` or
+/// `Rc<dyn SerializableLayer>` elements (anything borrowing as a
+/// `dyn SerializableLayer`), so callers holding either smart pointer can call
+/// this directly instead of rebuilding the slice.
+///
 /// # Example
 ///
 /// # Arguments
@@ -301,48 +411,162 @@ impl<'a> SerializeableBuffer for SerializeBuffer {
 ///
 /// This function returns a `Result<(), Box<dyn Error>>`. On success, it returns `Ok(())`.
 /// On failure, it returns an `Err` with the error that occurred during serialization.
-pub fn serialize_layers(
+pub fn serialize_layers<L: std::borrow::Borrow<dyn SerializableLayer>>(
     buffer: &mut SerializeBuffer,
     options: SerializeOptions,
-    layers: &[Box<dyn SerializableLayer>],
+    layers: &[L],
 ) -> Result<(), Box<dyn Error>> {
     buffer.clear();
     for layer in layers.iter().rev() {
+        let layer = layer.borrow();
         layer.serialize_to(buffer, options.clone())?;
         buffer.push_layer(layer.layer_type());
     }
     Ok(())
 }
 
-// pub fn serialize_packet(buffer: &mut dyn SerializeableBuffer, options: SerializeOptions, packet: &Packet) -> Result<(), Box<dyn Error>> {
-//     let mut serializable_layers: Vec<Box<dyn SerializableLayer>> = Vec::new();
-//
-//     for layer in packet.layers() {
-//         if let Some(sl) = layer.as_any().downcast_ref::<Box<dyn SerializableLayer>>() {
-//             serializable_layers.push(sl.clone());
-//         } else {
-//             return Err(Box::new(SerializeError::new(format!("Layer {:?} is not serializable", layer.layer_type()))));
-//         }
-//     }
-//
-//     serialize_layers(buffer, options, &serializable_layers)
-// }
-
-// Error type for serialization errors
+/// Re-serializes a decoded packet back into `buffer`.
+///
+/// Walks `packet.layers()` in order, recovering each layer's
+/// `SerializableLayer` view via `Layer::as_serializable`. If any layer does
+/// not support serialization, this returns a `SerializeError` naming that
+/// layer's type rather than silently dropping it. On success, delegates to
+/// `serialize_layers` so the layers are written innermost-to-outermost
+/// exactly as `serialize_layers` would for a hand-built layer list.
+pub fn serialize_packet(
+    buffer: &mut SerializeBuffer,
+    options: SerializeOptions,
+    packet: &dyn crate::rtpacket::packet::Packetable,
+) -> Result<(), Box<dyn Error>> {
+    let mut serializable_layers: Vec<Rc<dyn SerializableLayer>> = Vec::new();
+
+    for layer in packet.layers() {
+        match layer.as_serializable() {
+            Some(serializable) => serializable_layers.push(serializable),
+            None => {
+                return Err(Box::new(SerializeError::new(format!(
+                    "layer {:?} is not serializable",
+                    layer.layer_type().name
+                ))));
+            }
+        }
+    }
+
+    serialize_layers(buffer, options, &serializable_layers)
+}
+
+/// Plans and serializes `layers` (outermost-first, as in [`serialize_layers`])
+/// wrapping an innermost payload of `payload_len` bytes, in two phases:
+///
+/// 1. Walk `layers` innermost-first, starting from a body of `payload_len`
+///    bytes, validating each layer's [`LayerConstraints::min_body_len`]/
+///    `max_body_len` against the body it would wrap and returning a
+///    [`SerializeError`] naming the offending layer if it's out of range.
+///    Along the way, sum every layer's `header_len`/`footer_len`.
+/// 2. Allocate a single `SerializeBuffer` sized to fit the entire packet
+///    exactly, then run the normal prepend/append serialization via
+///    [`serialize_layers`].
+///
+/// Because the buffer's prefix and suffix are reserved up front, step 2 never
+/// reallocates, unlike calling `serialize_layers` directly with a fresh
+/// `SerializeBuffer::new()`.
+pub fn serialize_nested(
+    layers: &[Box<dyn SerializableLayer>],
+    payload_len: usize,
+    options: SerializeOptions,
+) -> Result<SerializeBuffer, Box<dyn Error>> {
+    let mut body_len = payload_len;
+    let mut total_header_len = 0usize;
+    let mut total_footer_len = 0usize;
+
+    for layer in layers.iter().rev() {
+        let constraints = layer.constraints();
+        if body_len < constraints.min_body_len || body_len > constraints.max_body_len {
+            return Err(Box::new(SerializeError::new(format!(
+                "layer {:?} cannot wrap a body of {} byte(s) (must be between {} and {})",
+                layer.layer_type().name,
+                body_len,
+                constraints.min_body_len,
+                constraints.max_body_len
+            ))));
+        }
+        total_header_len += constraints.header_len;
+        total_footer_len += constraints.footer_len;
+        body_len += constraints.header_len + constraints.footer_len;
+    }
+
+    let mut buffer = SerializeBuffer::new_default(total_header_len + payload_len, total_footer_len);
+    serialize_layers(&mut buffer, options, layers)?;
+    Ok(buffer)
+}
+
+/// Returns whether `length` fits in an unsigned 16-bit header field.
+///
+/// `serialize_to` implementations should call this (or [`fits_in_u32`])
+/// before casting `buffer.bytes().len()` into a length field while
+/// `SerializeOptions::fix_lengths` is set, and return
+/// [`SerializeError::length_overflow`] instead of truncating the value.
+pub fn fits_in_u16(length: usize) -> bool {
+    length <= u16::MAX as usize
+}
+
+/// Returns whether `length` fits in an unsigned 32-bit header field.
+///
+/// See [`fits_in_u16`] for how this is meant to be used.
+pub fn fits_in_u32(length: usize) -> bool {
+    length <= u32::MAX as usize
+}
+
+/// Error type for serialization errors.
 #[derive(Debug)]
-struct SerializeError {
-    message: String,
+pub enum SerializeError {
+    /// A generic serialization failure, e.g. a layer that cannot be
+    /// serialized at all.
+    Message(String),
+
+    /// A layer's length field is too narrow to represent the length of the
+    /// data that `fix_lengths` computed for it. Returned instead of
+    /// silently truncating the value into the field.
+    LengthOverflow {
+        /// Name of the layer type whose length field overflowed.
+        layer_type: String,
+        /// Width, in bits, of the length field that can't hold `length`.
+        field_bits: u8,
+        /// The length that was computed and didn't fit.
+        length: usize,
+    },
 }
 
 impl SerializeError {
     fn new(message: String) -> SerializeError {
-        SerializeError { message }
+        SerializeError::Message(message)
+    }
+
+    /// Constructs a [`SerializeError::LengthOverflow`] for a layer whose
+    /// `field_bits`-wide length field can't represent `length`.
+    pub fn length_overflow(layer_type: String, field_bits: u8, length: usize) -> SerializeError {
+        SerializeError::LengthOverflow {
+            layer_type,
+            field_bits,
+            length,
+        }
     }
 }
 
 impl fmt::Display for SerializeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "SerializeError: {}", self.message)
+        match self {
+            SerializeError::Message(message) => write!(f, "SerializeError: {}", message),
+            SerializeError::LengthOverflow {
+                layer_type,
+                field_bits,
+                length,
+            } => write!(
+                f,
+                "SerializeError: {} layer's {}-bit length field cannot represent a length of {} bytes",
+                layer_type, field_bits, length
+            ),
+        }
     }
 }
 
@@ -395,9 +619,9 @@ mod tests {
 
         b.clear(); // Assuming clear is similar to Clear
         assert_eq!(
-            b.start, 32,
+            b.body_start, 32,
             "Expected start to be 32 after clear, got {}",
-            b.start
+            b.body_start
         );
     }
 
@@ -430,6 +654,136 @@ mod tests {
         }
 
         b.clear();
-        assert_eq!(b.start, 0, "Expected start position 0, got {}", b.start);
+        assert_eq!(
+            b.body_start, 0,
+            "Expected start position 0, got {}",
+            b.body_start
+        );
+    }
+
+    #[test]
+    fn reset_for_reuse_stabilizes_capacity_across_cycles() {
+        let mut b = SerializeBuffer::new_default(0, 0);
+
+        // Warm-up: each cycle prepends/appends more than any prior cycle,
+        // forcing the backing allocation to grow.
+        for size in [4, 8, 16] {
+            b.prepend_bytes(size).expect("prepend should succeed");
+            b.append_bytes(size).expect("append should succeed");
+            b.reset_for_reuse();
+        }
+
+        let warm_capacity = b.data.capacity();
+
+        // Steady state: repeating the largest size seen during warm-up
+        // should no longer grow the backing allocation, so a caller
+        // serializing packet after packet amortizes allocation to zero.
+        for cycle in 0..5 {
+            b.prepend_bytes(16).expect("prepend should succeed");
+            b.append_bytes(16).expect("append should succeed");
+            assert_eq!(
+                b.data.capacity(),
+                warm_capacity,
+                "cycle {}: capacity should stabilize once warmed up",
+                cycle
+            );
+            b.reset_for_reuse();
+            assert!(b.layers().is_empty());
+        }
+    }
+
+    #[test]
+    fn fits_in_u16_rejects_oversized_lengths() {
+        assert!(fits_in_u16(0));
+        assert!(fits_in_u16(u16::MAX as usize));
+        assert!(!fits_in_u16(u16::MAX as usize + 1));
+    }
+
+    #[test]
+    fn fits_in_u32_rejects_oversized_lengths() {
+        assert!(fits_in_u32(0));
+        assert!(fits_in_u32(u32::MAX as usize));
+        assert!(!fits_in_u32(u32::MAX as usize + 1));
+    }
+
+    #[test]
+    fn length_overflow_error_reports_offending_length() {
+        let err = SerializeError::length_overflow("IPv4".to_owned(), 16, 70_000);
+        let message = err.to_string();
+        assert!(message.contains("IPv4"));
+        assert!(message.contains("70000"));
+    }
+
+    struct FixedHeaderLayer {
+        header_len: usize,
+        max_body_len: usize,
+    }
+
+    impl SerializableLayer for FixedHeaderLayer {
+        fn serialize_to(
+            &self,
+            buffer: &mut SerializeBuffer,
+            _opts: SerializeOptions,
+        ) -> Result<(), Box<dyn Error>> {
+            let bytes = buffer.prepend_bytes(self.header_len)?;
+            bytes.fill(0xAB);
+            Ok(())
+        }
+
+        fn layer_type(&self) -> LayerType {
+            LayerType {
+                id: 0,
+                name: "FixedHeaderLayer".to_owned(),
+                decoder: crate::rtpacket::decode::decoder_builder(
+                    crate::rtpacket::layertype::LayerTypes::LayerTypePayload,
+                ),
+            }
+        }
+
+        fn constraints(&self) -> LayerConstraints {
+            LayerConstraints {
+                header_len: self.header_len,
+                footer_len: 0,
+                min_body_len: 0,
+                max_body_len: self.max_body_len,
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_nested_allocates_buffer_exactly_once() {
+        let layers: Vec<Box<dyn SerializableLayer>> = vec![
+            Box::new(FixedHeaderLayer {
+                header_len: 14,
+                max_body_len: usize::MAX,
+            }),
+            Box::new(FixedHeaderLayer {
+                header_len: 20,
+                max_body_len: usize::MAX,
+            }),
+        ];
+
+        let buffer = serialize_nested(&layers, 100, SerializeOptions::default())
+            .expect("serialization should succeed");
+
+        // Only the two headers are actually written (there's no payload-writing
+        // layer in this test), but the buffer was allocated up front with
+        // exactly enough room for both headers plus the 100-byte payload they
+        // wrap, so capacity already reflects the full packet size.
+        assert_eq!(buffer.bytes().len(), 14 + 20);
+        assert_eq!(buffer.data.capacity(), 14 + 20 + 100);
+    }
+
+    #[test]
+    fn serialize_nested_rejects_body_over_a_layers_max() {
+        let layers: Vec<Box<dyn SerializableLayer>> = vec![Box::new(FixedHeaderLayer {
+            header_len: 20,
+            max_body_len: 50,
+        })];
+
+        let err = serialize_nested(&layers, 100, SerializeOptions::default())
+            .expect_err("body exceeds the layer's max_body_len");
+
+        assert!(err.to_string().contains("FixedHeaderLayer"));
     }
 }
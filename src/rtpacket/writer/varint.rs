@@ -0,0 +1,219 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::rtpacket::writer::{SerializeBuffer, SerializeableBuffer};
+
+/// The longest a LEB128-encoded `u64` can be: 10 groups of 7 bits cover all
+/// 64 bits of the value (with one bit to spare), matching the limit other
+/// varint encodings (e.g. protobuf's) place on a 64-bit value.
+const MAX_VARINT_LEN: usize = 10;
+
+impl SerializeBuffer {
+    /// Appends `value` to the buffer as a LEB128 varint.
+    ///
+    /// Each byte carries 7 bits of the value, least-significant group first,
+    /// with the high bit set on every byte but the last to signal that
+    /// another byte follows.
+    pub fn write_varint(&mut self, mut value: u64) -> Result<(), Box<dyn Error>> {
+        let mut encoded = [0u8; MAX_VARINT_LEN];
+        let mut len = 0;
+
+        loop {
+            let mut byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value != 0 {
+                byte |= 0x80;
+            }
+            encoded[len] = byte;
+            len += 1;
+            if value == 0 {
+                break;
+            }
+        }
+
+        let dest = self.append_bytes(len)?;
+        dest.copy_from_slice(&encoded[..len]);
+        Ok(())
+    }
+
+    /// Zig-zag encodes `value` and appends it as a varint, so small-magnitude
+    /// negative numbers stay as compact as their positive counterparts
+    /// instead of expanding to a `u64`'s full width. See [`zigzag_encode`].
+    pub fn write_signed_varint(&mut self, value: i64) -> Result<(), Box<dyn Error>> {
+        self.write_varint(zigzag_encode(value))
+    }
+}
+
+/// Reads a LEB128 varint from the start of `data`.
+///
+/// # Returns
+///
+/// The decoded value and the number of bytes consumed from `data`. Callers
+/// decoding a sequence of fields should continue reading from `data` at that
+/// offset.
+///
+/// # Errors
+///
+/// Returns a [`VarintError::UnexpectedEnd`] if `data` runs out before a byte
+/// without the continuation bit is found, or a [`VarintError::TooLong`] if
+/// more than [`MAX_VARINT_LEN`] bytes are read, which would overflow a
+/// `u64`.
+pub fn read_varint(data: &[u8]) -> Result<(u64, usize), Box<dyn Error>> {
+    let mut value: u64 = 0;
+
+    for (i, &byte) in data.iter().enumerate() {
+        if i == MAX_VARINT_LEN {
+            return Err(Box::new(VarintError::TooLong));
+        }
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+
+    Err(Box::new(VarintError::UnexpectedEnd))
+}
+
+/// Reads a zig-zag encoded signed varint from the start of `data`. See
+/// [`zigzag_decode`] and [`read_varint`].
+pub fn read_signed_varint(data: &[u8]) -> Result<(i64, usize), Box<dyn Error>> {
+    let (value, len) = read_varint(data)?;
+    Ok((zigzag_decode(value), len))
+}
+
+/// Maps signed integers to unsigned ones so small-magnitude negative values
+/// stay small too: `0, -1, 1, -2, 2, ...` map to `0, 1, 2, 3, 4, ...`. Used to
+/// make [`SerializeBuffer::write_signed_varint`] compact for values near
+/// zero in either direction, rather than sign-extending a negative `i64`
+/// into a `u64` that always takes the maximum number of varint bytes.
+pub fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Inverse of [`zigzag_encode`].
+pub fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Errors returned by [`read_varint`].
+#[derive(Debug)]
+pub enum VarintError {
+    /// More than [`MAX_VARINT_LEN`] bytes were read without finding a byte
+    /// whose continuation bit was unset.
+    TooLong,
+    /// `data` ended before a byte without the continuation bit was found.
+    UnexpectedEnd,
+}
+
+impl fmt::Display for VarintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VarintError::TooLong => write!(
+                f,
+                "varint exceeds the maximum length of {} bytes",
+                MAX_VARINT_LEN
+            ),
+            VarintError::UnexpectedEnd => {
+                write!(f, "varint runs past the end of the buffer")
+            }
+        }
+    }
+}
+
+impl Error for VarintError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_then_read_varint_round_trips_single_byte_values() {
+        for value in [0u64, 1, 63, 127] {
+            let mut buffer = SerializeBuffer::new();
+            buffer.write_varint(value).unwrap();
+            assert_eq!(buffer.bytes().len(), 1, "value {} should fit in one byte", value);
+
+            let (decoded, len) = read_varint(buffer.bytes()).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(len, 1);
+        }
+    }
+
+    #[test]
+    fn write_then_read_varint_round_trips_multi_byte_values() {
+        for value in [128u64, 300, u32::MAX as u64, u64::MAX] {
+            let mut buffer = SerializeBuffer::new();
+            buffer.write_varint(value).unwrap();
+
+            let (decoded, len) = read_varint(buffer.bytes()).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(len, buffer.bytes().len());
+        }
+    }
+
+    #[test]
+    fn u64_max_takes_ten_bytes() {
+        let mut buffer = SerializeBuffer::new();
+        buffer.write_varint(u64::MAX).unwrap();
+        assert_eq!(buffer.bytes().len(), MAX_VARINT_LEN);
+    }
+
+    #[test]
+    fn read_varint_consumes_only_its_own_bytes_from_a_longer_slice() {
+        let mut buffer = SerializeBuffer::new();
+        buffer.write_varint(300).unwrap();
+        buffer.append_bytes(2).unwrap().copy_from_slice(&[0xAA, 0xBB]);
+
+        let (decoded, len) = read_varint(buffer.bytes()).unwrap();
+        assert_eq!(decoded, 300);
+        assert_eq!(&buffer.bytes()[len..], &[0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn read_varint_rejects_truncated_input() {
+        // The continuation bit is set but no further byte follows.
+        let result = read_varint(&[0x80]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn read_varint_rejects_empty_input() {
+        let result = read_varint(&[]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn read_varint_rejects_more_than_ten_bytes() {
+        let too_long = [0x80u8; 11];
+        let result = read_varint(&too_long);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn zigzag_round_trips_positive_and_negative_values() {
+        for value in [0i64, -1, 1, -2, 2, i64::MIN, i64::MAX] {
+            assert_eq!(zigzag_decode(zigzag_encode(value)), value);
+        }
+    }
+
+    #[test]
+    fn zigzag_keeps_small_magnitude_values_compact() {
+        assert_eq!(zigzag_encode(0), 0);
+        assert_eq!(zigzag_encode(-1), 1);
+        assert_eq!(zigzag_encode(1), 2);
+        assert_eq!(zigzag_encode(-2), 3);
+        assert_eq!(zigzag_encode(2), 4);
+    }
+
+    #[test]
+    fn write_then_read_signed_varint_round_trips() {
+        for value in [0i64, -1, 1, -12345, 12345, i64::MIN, i64::MAX] {
+            let mut buffer = SerializeBuffer::new();
+            buffer.write_signed_varint(value).unwrap();
+
+            let (decoded, len) = read_signed_varint(buffer.bytes()).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(len, buffer.bytes().len());
+        }
+    }
+}